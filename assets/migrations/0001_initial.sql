@@ -1,6 +1,6 @@
 -- todo adjust types
 
-CREATE TABLE transactions (
+CREATE TABLE IF NOT EXISTS transactions (
     id SERIAL PRIMARY KEY,
     type VARCHAR NOT NULL,
     job_seq_number INTEGER NOT NULL,
@@ -12,20 +12,20 @@ CREATE TABLE transactions (
     provider VARCHAR
 );
 
-CREATE TABLE jobs (
+CREATE TABLE IF NOT EXISTS jobs (
     id SERIAL PRIMARY KEY, 
     transaction_hashes VARCHAR[] NOT NULL, 
     seq_number INTEGER NOT NULL UNIQUE,
     timestamp INTEGER -- or date?
 );
 
-CREATE INDEX idx_jobs_seq_number ON jobs (seq_number);
+CREATE INDEX IF NOT EXISTS idx_jobs_seq_number ON jobs (seq_number);
 
-CREATE TABLE state (
+CREATE TABLE IF NOT EXISTS state (
     id SERIAL PRIMARY KEY,
     key_name VARCHAR UNIQUE NOT NULL,
     last_processed_key INTEGER,
     updated_at TIMESTAMP DEFAULT NOW()
 );
 
-CREATE INDEX idx_transactions_hash ON transactions USING HASH (hash);
+CREATE INDEX IF NOT EXISTS idx_transactions_hash ON transactions USING HASH (hash);
@@ -0,0 +1,12 @@
+-- Final scores of each compute job, as returned by sequencer_get_results.
+CREATE TABLE IF NOT EXISTS scores (
+    id SERIAL PRIMARY KEY,
+    job_seq_number INTEGER NOT NULL,
+    -- position of the entry in the sequencer's result list, used to resume paging
+    position INTEGER NOT NULL,
+    peer_id VARCHAR NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    UNIQUE (job_seq_number, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_scores_job_peer ON scores (job_seq_number, peer_id);
@@ -0,0 +1,19 @@
+-- Score entries of each ComputeScores transaction, one row per peer.
+CREATE TABLE IF NOT EXISTS score_entries (
+    id SERIAL PRIMARY KEY,
+    job_seq_number INTEGER NOT NULL,
+    tx_hash VARCHAR NOT NULL,
+    peer_id VARCHAR NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    UNIQUE (tx_hash, peer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_score_entries_peer_id ON score_entries (peer_id);
+CREATE INDEX IF NOT EXISTS idx_score_entries_job_value ON score_entries (job_seq_number, value DESC);
+
+-- Backfill the entries of jobs indexed before this migration.
+INSERT INTO score_entries (job_seq_number, tx_hash, peer_id, value)
+SELECT job_seq_number, hash, entry ->> 'id', (entry ->> 'value')::DOUBLE PRECISION
+FROM transactions, jsonb_array_elements(body -> 'ComputeScores' -> 'entries') AS entry
+WHERE type = 'compute_scores'
+ON CONFLICT (tx_hash, peer_id) DO NOTHING;
@@ -0,0 +1,25 @@
+-- Trust graph edges from TrustUpdate transactions.
+CREATE TABLE IF NOT EXISTS trust_edges (
+    id SERIAL PRIMARY KEY,
+    tx_hash VARCHAR NOT NULL,
+    trust_id VARCHAR NOT NULL,
+    "from" VARCHAR NOT NULL,
+    "to" VARCHAR NOT NULL,
+    weight DOUBLE PRECISION NOT NULL,
+    UNIQUE (tx_hash, "from", "to")
+);
+
+CREATE INDEX IF NOT EXISTS idx_trust_edges_from ON trust_edges (trust_id, "from");
+CREATE INDEX IF NOT EXISTS idx_trust_edges_to ON trust_edges (trust_id, "to");
+
+-- Seed trust entries from SeedUpdate transactions.
+CREATE TABLE IF NOT EXISTS seed_entries (
+    id SERIAL PRIMARY KEY,
+    tx_hash VARCHAR NOT NULL,
+    seed_id VARCHAR NOT NULL,
+    peer_id VARCHAR NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    UNIQUE (tx_hash, peer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_seed_entries_peer_id ON seed_entries (seed_id, peer_id);
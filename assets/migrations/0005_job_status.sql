@@ -0,0 +1,49 @@
+-- One row per verifier vote on a job.
+CREATE TABLE IF NOT EXISTS verifications (
+    id SERIAL PRIMARY KEY,
+    job_seq_number INTEGER NOT NULL,
+    tx_hash VARCHAR NOT NULL UNIQUE,
+    verifier VARCHAR NOT NULL,
+    result BOOLEAN NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_verifications_job_seq_number ON verifications (job_seq_number);
+
+-- requested, assigned, committed, verified or rejected. Every job indexed so far has a
+-- commitment, since the sequencer only returns compute results for committed jobs.
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS status VARCHAR NOT NULL DEFAULT 'committed';
+
+-- Backfill the votes and statuses of jobs indexed before this migration.
+INSERT INTO verifications (job_seq_number, tx_hash, verifier, result)
+SELECT job_seq_number, hash, "from",
+       (body -> 'ComputeVerification' ->> 'verification_result')::BOOLEAN
+FROM transactions
+WHERE type = 'compute_verification'
+  AND body -> 'ComputeVerification' ->> 'verification_result' IS NOT NULL
+ON CONFLICT (tx_hash) DO NOTHING;
+
+-- A job stays committed until the assigned verifiers that have not voted yet can no
+-- longer change the majority; a tie counts as rejected.
+UPDATE jobs
+SET status = CASE
+        WHEN votes.approvals > votes.rejections + votes.outstanding THEN 'verified'
+        WHEN votes.rejections >= votes.approvals + votes.outstanding THEN 'rejected'
+        ELSE 'committed'
+    END
+FROM (
+    SELECT v.job_seq_number,
+           COUNT(*) FILTER (WHERE v.result) AS approvals,
+           COUNT(*) FILTER (WHERE NOT v.result) AS rejections,
+           (SELECT COUNT(*)
+            FROM transactions AS a,
+                 jsonb_array_elements_text(
+                     a.body -> 'ComputeAssignment' -> 'assigned_verifier_nodes') AS node
+            WHERE a.job_seq_number = v.job_seq_number
+              AND a.type = 'compute_assignment'
+              AND node NOT IN (
+                  SELECT w.verifier FROM verifications AS w
+                  WHERE w.job_seq_number = v.job_seq_number)) AS outstanding
+    FROM verifications AS v
+    GROUP BY v.job_seq_number
+) AS votes
+WHERE jobs.seq_number = votes.job_seq_number;
@@ -0,0 +1,35 @@
+-- Widen ids, seq numbers, cursors and positions to 64 bits.
+ALTER TABLE transactions ALTER COLUMN id TYPE BIGINT, ALTER COLUMN job_seq_number TYPE BIGINT;
+ALTER SEQUENCE transactions_id_seq AS BIGINT;
+
+ALTER TABLE jobs ALTER COLUMN id TYPE BIGINT, ALTER COLUMN seq_number TYPE BIGINT;
+ALTER SEQUENCE jobs_id_seq AS BIGINT;
+
+ALTER TABLE state ALTER COLUMN id TYPE BIGINT, ALTER COLUMN last_processed_key TYPE BIGINT;
+ALTER SEQUENCE state_id_seq AS BIGINT;
+
+ALTER TABLE scores
+    ALTER COLUMN id TYPE BIGINT,
+    ALTER COLUMN job_seq_number TYPE BIGINT,
+    ALTER COLUMN position TYPE BIGINT;
+ALTER SEQUENCE scores_id_seq AS BIGINT;
+
+ALTER TABLE score_entries ALTER COLUMN id TYPE BIGINT, ALTER COLUMN job_seq_number TYPE BIGINT;
+ALTER SEQUENCE score_entries_id_seq AS BIGINT;
+
+ALTER TABLE verifications ALTER COLUMN id TYPE BIGINT, ALTER COLUMN job_seq_number TYPE BIGINT;
+ALTER SEQUENCE verifications_id_seq AS BIGINT;
+
+ALTER TABLE trust_edges ALTER COLUMN id TYPE BIGINT;
+ALTER SEQUENCE trust_edges_id_seq AS BIGINT;
+
+ALTER TABLE seed_entries ALTER COLUMN id TYPE BIGINT;
+ALTER SEQUENCE seed_entries_id_seq AS BIGINT;
+
+-- Job times become real timestamps. The old 32-bit column held truncated milliseconds,
+-- which cannot be turned back into dates, so they are cleared; the indexer fills them in
+-- again from the sequencer.
+ALTER TABLE jobs ALTER COLUMN timestamp TYPE TIMESTAMPTZ USING NULL;
+CREATE INDEX IF NOT EXISTS idx_jobs_missing_timestamp ON jobs (seq_number)
+    WHERE timestamp IS NULL;
+ALTER TABLE state ALTER COLUMN updated_at TYPE TIMESTAMPTZ;
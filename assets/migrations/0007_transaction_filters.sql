@@ -0,0 +1,6 @@
+-- Indexes backing the filters of the `transactions` query.
+CREATE INDEX IF NOT EXISTS idx_transactions_type ON transactions (type);
+CREATE INDEX IF NOT EXISTS idx_transactions_from ON transactions ("from");
+CREATE INDEX IF NOT EXISTS idx_transactions_to ON transactions ("to");
+CREATE INDEX IF NOT EXISTS idx_transactions_job_seq_number ON transactions (job_seq_number);
+CREATE INDEX IF NOT EXISTS idx_jobs_timestamp ON jobs (timestamp);
@@ -0,0 +1,6 @@
+-- Ranking of the scores of a job, highest first, and the lookup of jobs by domain. The
+-- value is negated so that ranking keysets compare as one row, `(-value, position)`.
+CREATE INDEX IF NOT EXISTS idx_scores_job_rank ON scores (job_seq_number, (-value), position);
+CREATE INDEX IF NOT EXISTS idx_transactions_request_domain
+    ON transactions ((body -> 'ComputeRequest' ->> 'domain_id'))
+    WHERE type = 'compute_request';
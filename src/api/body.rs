@@ -0,0 +1,200 @@
+//! Typed GraphQL view of transaction bodies, decoded from the JSON they are stored as.
+
+use async_graphql::{SimpleObject, Union};
+use openrank_common::tx::{compute, trust, Body};
+use openrank_relayer::types::to_hex_string;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Body of a transaction, one object type per kind.
+#[derive(Union)]
+pub enum TxBody {
+    ComputeRequest(ComputeRequest),
+    ComputeAssignment(ComputeAssignment),
+    ComputeScores(ComputeScores),
+    ComputeCommitment(ComputeCommitment),
+    ComputeVerification(ComputeVerification),
+    TrustUpdate(TrustUpdate),
+    SeedUpdate(SeedUpdate),
+}
+
+#[derive(SimpleObject)]
+pub struct ComputeRequest {
+    pub domain_id: String,
+    pub block_height: u32,
+    pub compute_id: String,
+}
+
+#[derive(SimpleObject)]
+pub struct ComputeAssignment {
+    pub request_tx_hash: String,
+    pub assigned_compute_node: String,
+    pub assigned_verifier_nodes: Vec<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ComputeScores {
+    pub entries: Vec<ScoreEntry>,
+}
+
+#[derive(SimpleObject)]
+pub struct ComputeCommitment {
+    pub assignment_tx_hash: String,
+    pub lt_root_hash: String,
+    pub compute_root_hash: String,
+    pub scores_tx_hashes: Vec<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ComputeVerification {
+    pub assignment_tx_hash: String,
+    pub verification_result: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct TrustUpdate {
+    pub trust_id: String,
+    pub entries: Vec<TrustEntry>,
+}
+
+#[derive(SimpleObject)]
+pub struct SeedUpdate {
+    pub seed_id: String,
+    pub entries: Vec<ScoreEntry>,
+}
+
+/// Score of a peer, in a ComputeScores or SeedUpdate transaction.
+#[derive(SimpleObject)]
+pub struct ScoreEntry {
+    pub id: String,
+    pub value: f64,
+}
+
+/// Weighted edge of the trust graph, in a TrustUpdate transaction.
+#[derive(SimpleObject)]
+pub struct TrustEntry {
+    pub from: String,
+    pub to: String,
+    pub value: f64,
+}
+
+impl TryFrom<&Value> for TxBody {
+    type Error = serde_json::Error;
+
+    fn try_from(raw: &Value) -> Result<Self, Self::Error> {
+        let body = match Body::deserialize(raw)? {
+            Body::ComputeRequest(request) => TxBody::ComputeRequest(request.try_into()?),
+            Body::ComputeAssignment(assignment) => {
+                TxBody::ComputeAssignment(assignment.try_into()?)
+            },
+            Body::ComputeScores(scores) => TxBody::ComputeScores(scores.into()),
+            Body::ComputeCommitment(commitment) => {
+                TxBody::ComputeCommitment(commitment.try_into()?)
+            },
+            Body::ComputeVerification(verification) => {
+                TxBody::ComputeVerification(verification.try_into()?)
+            },
+            Body::TrustUpdate(update) => TxBody::TrustUpdate(update.try_into()?),
+            Body::SeedUpdate(update) => TxBody::SeedUpdate(update.try_into()?),
+            _ => return Err(serde::de::Error::custom("unsupported transaction body")),
+        };
+        Ok(body)
+    }
+}
+
+impl TryFrom<compute::Request> for ComputeRequest {
+    type Error = serde_json::Error;
+
+    fn try_from(request: compute::Request) -> Result<Self, Self::Error> {
+        Ok(ComputeRequest {
+            domain_id: to_hex_string(request.domain_id())?,
+            block_height: *request.block_height(),
+            compute_id: to_hex_string(request.compute_id())?,
+        })
+    }
+}
+
+impl TryFrom<compute::Assignment> for ComputeAssignment {
+    type Error = serde_json::Error;
+
+    fn try_from(assignment: compute::Assignment) -> Result<Self, Self::Error> {
+        Ok(ComputeAssignment {
+            request_tx_hash: to_hex_string(assignment.request_tx_hash())?,
+            assigned_compute_node: to_hex_string(assignment.assigned_compute_node())?,
+            assigned_verifier_nodes: assignment
+                .assigned_verifier_nodes()
+                .iter()
+                .map(to_hex_string)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<compute::Scores> for ComputeScores {
+    fn from(scores: compute::Scores) -> Self {
+        let entries = scores
+            .entries()
+            .iter()
+            .map(|entry| ScoreEntry { id: entry.id().clone(), value: f64::from(*entry.value()) })
+            .collect();
+        ComputeScores { entries }
+    }
+}
+
+impl TryFrom<compute::Commitment> for ComputeCommitment {
+    type Error = serde_json::Error;
+
+    fn try_from(commitment: compute::Commitment) -> Result<Self, Self::Error> {
+        Ok(ComputeCommitment {
+            assignment_tx_hash: to_hex_string(commitment.assignment_tx_hash())?,
+            lt_root_hash: to_hex_string(commitment.lt_root_hash())?,
+            compute_root_hash: to_hex_string(commitment.compute_root_hash())?,
+            scores_tx_hashes: commitment
+                .scores_tx_hashes()
+                .iter()
+                .map(to_hex_string)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<compute::Verification> for ComputeVerification {
+    type Error = serde_json::Error;
+
+    fn try_from(verification: compute::Verification) -> Result<Self, Self::Error> {
+        Ok(ComputeVerification {
+            assignment_tx_hash: to_hex_string(verification.assignment_tx_hash())?,
+            verification_result: *verification.verification_result(),
+        })
+    }
+}
+
+impl TryFrom<trust::TrustUpdate> for TrustUpdate {
+    type Error = serde_json::Error;
+
+    fn try_from(update: trust::TrustUpdate) -> Result<Self, Self::Error> {
+        let entries = update
+            .entries()
+            .iter()
+            .map(|entry| TrustEntry {
+                from: entry.from().clone(),
+                to: entry.to().clone(),
+                value: f64::from(*entry.value()),
+            })
+            .collect();
+        Ok(TrustUpdate { trust_id: to_hex_string(update.trust_id())?, entries })
+    }
+}
+
+impl TryFrom<trust::SeedUpdate> for SeedUpdate {
+    type Error = serde_json::Error;
+
+    fn try_from(update: trust::SeedUpdate) -> Result<Self, Self::Error> {
+        let entries = update
+            .entries()
+            .iter()
+            .map(|entry| ScoreEntry { id: entry.id().clone(), value: f64::from(*entry.value()) })
+            .collect();
+        Ok(SeedUpdate { seed_id: to_hex_string(update.seed_id())?, entries })
+    }
+}
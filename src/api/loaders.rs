@@ -0,0 +1,38 @@
+//! DataLoaders batching the lookups of nested GraphQL fields, so that resolving them for a
+//! page of nodes takes one query per field rather than one per node.
+
+use async_graphql::dataloader::Loader;
+use openrank_relayer::storage::{Job, Storage, Transaction};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Loads jobs by seq number.
+pub struct JobLoader(pub Storage);
+
+impl Loader<i64> for JobLoader {
+    type Value = Job;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, seq_numbers: &[i64]) -> Result<HashMap<i64, Job>, Self::Error> {
+        let jobs = self.0.jobs_by_seq_numbers(seq_numbers).await?;
+        Ok(jobs.into_iter().map(|job| (job.seq_number, job)).collect())
+    }
+}
+
+/// Loads the transactions of jobs by job seq number, ordered by id.
+pub struct JobTransactionsLoader(pub Storage);
+
+impl Loader<i64> for JobTransactionsLoader {
+    type Value = Vec<Transaction>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(
+        &self, seq_numbers: &[i64],
+    ) -> Result<HashMap<i64, Vec<Transaction>>, Self::Error> {
+        let mut transactions: HashMap<i64, Vec<Transaction>> = HashMap::new();
+        for tx in self.0.transactions_by_jobs(seq_numbers).await? {
+            transactions.entry(tx.job_seq_number).or_default().push(tx);
+        }
+        Ok(transactions)
+    }
+}
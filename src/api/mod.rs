@@ -1,3 +1,4 @@
-pub mod connect;
+pub mod body;
+pub mod loaders;
 pub mod schema;
 pub mod server;
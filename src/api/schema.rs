@@ -1,92 +1,445 @@
-use async_graphql::{Context, Object, Schema, SimpleObject};
+use crate::api::body::TxBody;
+use crate::api::loaders::{JobLoader, JobTransactionsLoader};
+use async_graphql::connection::{self, Connection, Edge, OpaqueCursor};
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{ComplexObject, Context, Enum, InputObject, Object, Schema, SimpleObject};
 use async_graphql::{EmptyMutation, EmptySubscription};
+use chrono::{DateTime, Utc};
+use openrank_relayer::storage::{self, Page, Paged, ScoreKey, Storage};
+use openrank_relayer::types;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_json::Value;
-use sqlx::{query_as, PgPool};
 
-const DEFAULT_LIMIT: i32 = 10;
+const DEFAULT_LIMIT: usize = 10;
 
 pub struct QueryRoot;
 
 #[Object]
 impl QueryRoot {
+    /// Transactions matching `filter`, ordered by id, as a Relay connection.
     async fn transactions(
-        &self, ctx: &Context<'_>, limit: Option<i32>, offset: Option<i32>, hash: Option<String>,
-        job_seq_number: Option<i32>,
-    ) -> Vec<Transaction> {
-        let pool = ctx.data::<PgPool>().unwrap();
+        &self, ctx: &Context<'_>, after: Option<String>, before: Option<String>,
+        first: Option<i32>, last: Option<i32>, direction: Option<Direction>,
+        filter: Option<TransactionFilter>,
+    ) -> async_graphql::Result<Connection<i64, Transaction, TransactionsFields>> {
+        let storage = ctx.data::<Storage>()?;
+        let filter = filter.map(storage::TransactionFilter::from).unwrap_or_default();
 
-        let limit = limit.unwrap_or(DEFAULT_LIMIT);
-        let offset = offset.unwrap_or(0);
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let page = page(after, before, first, last, direction);
+                let transactions = storage.list_transactions(&filter, &page).await?;
+                Ok::<_, async_graphql::Error>(into_connection(
+                    &page,
+                    transactions,
+                    TransactionsFields { filter },
+                    |tx| tx.id,
+                ))
+            },
+        )
+        .await
+    }
 
-        let mut sql = String::from("SELECT id, body, type, hash, job_seq_number, \"to\", \"from\" FROM transactions WHERE 1=1");
-        let mut index = 1;
+    /// Scores of a job by rank, highest first, as a Relay connection paged forwards. Without
+    /// `job_seq`, the latest job whose scores are all indexed is used, computed for `domain`
+    /// if given.
+    async fn top_scores(
+        &self, ctx: &Context<'_>, job_seq: Option<i64>, domain: Option<String>,
+        after: Option<String>, first: Option<i32>,
+    ) -> async_graphql::Result<Option<ScoresConnection>> {
+        let storage = ctx.data::<Storage>()?;
+        let Some(job_seq_number) = resolve_scored_job(storage, job_seq, domain).await? else {
+            return Ok(None);
+        };
 
-        if hash.is_some() {
-            sql.push_str(&format!(" AND hash = ${}", index));
-            index += 1;
-        }
-        if job_seq_number.is_some() {
-            sql.push_str(&format!(" AND job_seq_number = ${}", index));
-            index += 1;
-        }
+        let connection = connection::query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<OpaqueCursor<ScoreKey>>, _, first, _| {
+                scores_connection(storage, job_seq_number, after.map(|cursor| cursor.0), first)
+            },
+        )
+        .await?;
+        Ok(Some(connection))
+    }
 
-        sql.push_str(&format!(" LIMIT ${} OFFSET ${}", index, index + 1));
+    /// Score and rank of a peer in a job, or in the latest job whose scores are all indexed.
+    async fn score(
+        &self, ctx: &Context<'_>, peer_id: String, job_seq: Option<i64>,
+    ) -> async_graphql::Result<Option<RankedScore>> {
+        let storage = ctx.data::<Storage>()?;
+        let Some(job_seq_number) = resolve_scored_job(storage, job_seq, None).await? else {
+            return Ok(None);
+        };
 
-        let mut query = query_as::<_, Transaction>(&sql);
+        let score = storage.peer_score(job_seq_number, &peer_id).await?;
+        Ok(score.map(RankedScore::from))
+    }
 
-        if let Some(h) = hash {
-            query = query.bind(h);
-        }
-        if let Some(jsn) = job_seq_number {
-            query = query.bind(jsn);
-        }
-        query = query.bind(limit).bind(offset);
+    /// The `first` highest scores of the latest job whose scores are all indexed, computed
+    /// for `domain` if given.
+    async fn latest_scores(
+        &self, ctx: &Context<'_>, domain: Option<String>, first: Option<i32>,
+    ) -> async_graphql::Result<Vec<RankedScore>> {
+        let storage = ctx.data::<Storage>()?;
+        let Some(job_seq_number) = resolve_scored_job(storage, None, domain).await? else {
+            return Ok(Vec::new());
+        };
 
-        query.fetch_all(pool).await.unwrap()
+        let limit = first.map_or(Ok(DEFAULT_LIMIT), usize::try_from)? as i64;
+        let scores = storage.top_scores(job_seq_number, None, limit).await?;
+        Ok(scores.rows.into_iter().map(RankedScore::from).collect())
     }
 
-    async fn jobs(&self, ctx: &Context<'_>, limit: Option<i32>, offset: Option<i32>) -> Vec<Job> {
-        let pool = ctx.data::<PgPool>().unwrap();
-
-        let limit = limit.unwrap_or(DEFAULT_LIMIT);
-        let offset = offset.unwrap_or(0);
+    /// Jobs ordered by seq number, as a Relay connection.
+    async fn jobs(
+        &self, ctx: &Context<'_>, after: Option<String>, before: Option<String>,
+        first: Option<i32>, last: Option<i32>, direction: Option<Direction>,
+    ) -> async_graphql::Result<Connection<i64, Job, JobsFields>> {
+        let storage = ctx.data::<Storage>()?;
 
-        sqlx::query_as::<_, Job>(
-            "SELECT id, transaction_hashes, seq_number, timestamp FROM jobs LIMIT $1 OFFSET $2",
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after, before, first, last| async move {
+                let page = page(after, before, first, last, direction);
+                let jobs = storage.list_jobs(&page).await?;
+                Ok::<_, async_graphql::Error>(into_connection(&page, jobs, JobsFields, |job| {
+                    job.seq_number
+                }))
+            },
         )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
         .await
-        .unwrap()
+    }
+}
+
+/// The job asked for, or else the latest one with all its scores indexed.
+async fn resolve_scored_job(
+    storage: &Storage, job_seq: Option<i64>, domain: Option<String>,
+) -> Result<Option<i64>, sqlx::Error> {
+    match job_seq {
+        Some(job_seq) => Ok(Some(job_seq)),
+        None => storage.latest_scored_job(domain.as_deref()).await,
+    }
+}
+
+/// Storage page for the Relay arguments. `last` reads from the end of the window; with
+/// neither `first` nor `last`, the first `DEFAULT_LIMIT` rows are returned.
+fn page(
+    after: Option<i64>, before: Option<i64>, first: Option<usize>, last: Option<usize>,
+    direction: Option<Direction>,
+) -> Page {
+    let (limit, from_end) = match (first, last) {
+        (Some(first), _) => (first, false),
+        (None, Some(last)) => (last, true),
+        (None, None) => (DEFAULT_LIMIT, false),
+    };
+    Page {
+        after,
+        before,
+        limit: limit as i64,
+        direction: direction.unwrap_or_default().into(),
+        from_end,
+    }
+}
+
+/// Builds a connection from a page of rows. Past the cursor a client pages from, there is
+/// always a page back; the other side has one if more rows were found.
+fn into_connection<T, N, F>(
+    page: &Page, paged: Paged<T>, fields: F, cursor: impl Fn(&T) -> i64,
+) -> Connection<i64, N, F>
+where
+    N: From<T> + async_graphql::OutputType,
+    F: async_graphql::ObjectType,
+{
+    let (has_previous_page, has_next_page) = if page.from_end {
+        (paged.has_more, page.before.is_some())
+    } else {
+        (page.after.is_some(), paged.has_more)
+    };
+    let mut connection =
+        Connection::with_additional_fields(has_previous_page, has_next_page, fields);
+    connection
+        .edges
+        .extend(paged.rows.into_iter().map(|row| Edge::new(cursor(&row), N::from(row))));
+    connection
+}
+
+/// Page of a job's scores ranked after `after`. Only forward paging is supported, so the
+/// previous page exists exactly when paging from a cursor.
+async fn scores_connection(
+    storage: &Storage, job_seq_number: i64, after: Option<ScoreKey>, first: Option<usize>,
+) -> async_graphql::Result<ScoresConnection> {
+    let limit = first.unwrap_or(DEFAULT_LIMIT) as i64;
+    let scores = storage.top_scores(job_seq_number, after, limit).await?;
+
+    let mut connection = Connection::with_additional_fields(
+        after.is_some(),
+        scores.has_more,
+        ScoresFields { job_seq_number },
+    );
+    connection.edges.extend(
+        scores
+            .rows
+            .into_iter()
+            .map(|score| Edge::new(OpaqueCursor(score.key()), RankedScore::from(score))),
+    );
+    Ok(connection)
+}
+
+/// Order a connection lists its nodes in.
+#[derive(Enum, Copy, Clone, Default, Eq, PartialEq)]
+#[graphql(remote = "openrank_relayer::storage::Direction")]
+pub enum Direction {
+    /// Oldest first.
+    #[default]
+    Forward,
+    /// Newest first.
+    Backward,
+}
+
+/// Filters of the `transactions` query. Every field given must match; ranges include their
+/// start and exclude their end.
+#[derive(InputObject)]
+pub struct TransactionFilter {
+    pub hash: Option<String>,
+    #[graphql(name = "type")]
+    pub type_: Option<TxType>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub job_seq_number: Option<i64>,
+    pub job_seq_from: Option<i64>,
+    pub job_seq_to: Option<i64>,
+    /// Bounds on the timestamp of the job the transaction belongs to.
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl From<TransactionFilter> for storage::TransactionFilter {
+    fn from(filter: TransactionFilter) -> Self {
+        storage::TransactionFilter {
+            hash: filter.hash,
+            kind: filter.type_.map(Into::into),
+            from: filter.from,
+            to: filter.to,
+            job_seq_number: filter.job_seq_number,
+            job_seq_from: filter.job_seq_from,
+            job_seq_to: filter.job_seq_to,
+            since: filter.since,
+            until: filter.until,
+        }
+    }
+}
+
+/// Kind of an indexed transaction.
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[graphql(remote = "openrank_relayer::types::TxKind")]
+pub enum TxType {
+    ComputeRequest,
+    ComputeAssignment,
+    ComputeScores,
+    ComputeCommitment,
+    ComputeVerification,
+}
+
+pub struct TransactionsFields {
+    filter: storage::TransactionFilter,
+}
+
+#[Object]
+impl TransactionsFields {
+    /// Number of transactions matching the filter, across all pages.
+    async fn total_count(&self, ctx: &Context<'_>) -> async_graphql::Result<i64> {
+        Ok(ctx.data::<Storage>()?.count_transactions(&self.filter).await?)
+    }
+}
+
+pub struct JobsFields;
+
+#[Object]
+impl JobsFields {
+    /// Number of jobs, across all pages.
+    async fn total_count(&self, ctx: &Context<'_>) -> async_graphql::Result<i64> {
+        Ok(ctx.data::<Storage>()?.count_jobs().await?)
+    }
+}
+
+/// Scores of a job, paged on their place in the ranking.
+type ScoresConnection = Connection<OpaqueCursor<ScoreKey>, RankedScore, ScoresFields>;
+
+pub struct ScoresFields {
+    job_seq_number: i64,
+}
+
+#[Object]
+impl ScoresFields {
+    /// The job the scores belong to.
+    async fn job_seq_number(&self) -> i64 {
+        self.job_seq_number
+    }
+
+    /// Number of scores of the job, across all pages.
+    async fn total_count(&self, ctx: &Context<'_>) -> async_graphql::Result<i64> {
+        Ok(ctx.data::<Storage>()?.count_scores(self.job_seq_number).await?)
+    }
+}
+
+/// Score of a peer in a job, with its rank among the scores of the job.
+#[derive(SimpleObject)]
+pub struct RankedScore {
+    pub job_seq_number: i64,
+    pub peer_id: String,
+    pub value: f64,
+    /// Position by descending value, 1 for the highest.
+    pub rank: i64,
+}
+
+impl From<storage::RankedScore> for RankedScore {
+    fn from(score: storage::RankedScore) -> Self {
+        RankedScore {
+            job_seq_number: score.job_seq_number,
+            peer_id: score.peer_id,
+            value: score.value,
+            rank: score.rank,
+        }
     }
 }
 
 // Define the Transaction struct to match the `transactions` table structure
-#[derive(SimpleObject, sqlx::FromRow, Serialize, Deserialize)]
+#[derive(SimpleObject, Serialize, Deserialize)]
+#[graphql(complex)]
 pub struct Transaction {
-    pub id: i32,
-    pub body: Value,
-    #[sqlx(rename = "type")]
+    pub id: i64,
+    /// The body as stored, in the JSON shape of the sequencer.
+    pub raw_body: Value,
     pub type_: String,
     pub hash: String,
-    pub job_seq_number: i32,
+    pub job_seq_number: i64,
     pub to: String,
     pub from: String,
 }
 
-#[derive(SimpleObject, sqlx::FromRow, Serialize, Deserialize)]
+impl From<storage::Transaction> for Transaction {
+    fn from(tx: storage::Transaction) -> Self {
+        Transaction {
+            id: tx.id,
+            raw_body: tx.body,
+            type_: tx.type_,
+            hash: tx.hash,
+            job_seq_number: tx.job_seq_number,
+            to: tx.to,
+            from: tx.from,
+        }
+    }
+}
+
+#[ComplexObject]
+impl Transaction {
+    /// The body, decoded into the object type of its kind.
+    async fn body(&self) -> async_graphql::Result<TxBody> {
+        Ok(TxBody::try_from(&self.raw_body)?)
+    }
+
+    /// The job the transaction belongs to.
+    async fn job(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Job>> {
+        let loader = ctx.data::<DataLoader<JobLoader>>()?;
+        Ok(loader.load_one(self.job_seq_number).await?.map(Job::from))
+    }
+}
+
+#[derive(SimpleObject, Serialize, Deserialize)]
+#[graphql(complex)]
 pub struct Job {
-    pub id: i32,
+    pub id: i64,
     pub transaction_hashes: Vec<String>,
-    pub seq_number: i32,
-    pub timestamp: i32,
+    pub seq_number: i64,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub status: JobStatus,
+}
+
+impl From<storage::Job> for Job {
+    fn from(job: storage::Job) -> Self {
+        Job {
+            id: job.id,
+            transaction_hashes: job.transaction_hashes,
+            seq_number: job.seq_number,
+            timestamp: job.timestamp,
+            status: job.status.into(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Job {
+    /// Every indexed transaction of the job, ordered by id.
+    async fn transactions(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Transaction>> {
+        self.load_transactions(ctx, None).await
+    }
+
+    /// The ComputeRequest transaction that started the job.
+    async fn request(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Transaction>> {
+        let kind = Some(types::TxKind::ComputeRequest);
+        Ok(self.load_transactions(ctx, kind).await?.into_iter().next())
+    }
+
+    /// The ComputeCommitment transaction of the job, once committed.
+    async fn commitment(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Transaction>> {
+        let kind = Some(types::TxKind::ComputeCommitment);
+        Ok(self.load_transactions(ctx, kind).await?.into_iter().next())
+    }
+
+    /// The ComputeVerification transactions of the job.
+    async fn verifications(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<Transaction>> {
+        self.load_transactions(ctx, Some(types::TxKind::ComputeVerification)).await
+    }
+}
+
+impl Job {
+    /// Transactions of the job, of the given kind only if any, through the batching loader.
+    async fn load_transactions(
+        &self, ctx: &Context<'_>, kind: Option<types::TxKind>,
+    ) -> async_graphql::Result<Vec<Transaction>> {
+        let loader = ctx.data::<DataLoader<JobTransactionsLoader>>()?;
+        let transactions = loader.load_one(self.seq_number).await?.unwrap_or_default();
+        Ok(transactions
+            .into_iter()
+            .filter(|tx| kind.map_or(true, |kind| tx.type_ == kind.as_str()))
+            .map(Transaction::from)
+            .collect())
+    }
+}
+
+/// Where a compute job is in its lifecycle. A job stays committed until its verifiers
+/// have decided it: it is verified once approvals are a majority the verifiers yet to vote
+/// cannot overturn, and rejected once they cannot outvote the rejections.
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[graphql(remote = "openrank_relayer::types::JobStatus")]
+pub enum JobStatus {
+    Requested,
+    Assigned,
+    Committed,
+    Verified,
+    Rejected,
 }
 
 pub type MySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
 
-pub fn build_schema(pool: PgPool) -> MySchema {
-    Schema::build(QueryRoot, EmptyMutation, EmptySubscription).data(pool).finish()
+pub fn build_schema(storage: Storage) -> MySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(DataLoader::new(JobLoader(storage.clone()), tokio::spawn))
+        .data(DataLoader::new(
+            JobTransactionsLoader(storage.clone()),
+            tokio::spawn,
+        ))
+        .data(storage)
+        .finish()
 }
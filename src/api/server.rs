@@ -1,18 +1,15 @@
-use crate::api::connect::get_db_pool;
 use crate::api::schema::build_schema;
 use crate::api::schema::MySchema;
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql_warp::GraphQLResponse;
-use dotenv::dotenv;
-use std::sync::Arc;
+use openrank_relayer::config::Config;
+use openrank_relayer::storage::Storage;
 use warp::http::Method;
 use warp::Filter;
 
-pub async fn serve() {
-    dotenv().ok();
-
-    let pool = Arc::new(get_db_pool().await);
-    let schema = build_schema((*pool).clone());
+pub async fn serve(config: Config) {
+    let storage = Storage::connect(&config.database).await.expect("Failed to create pool.");
+    let schema = build_schema(storage);
 
     let graphql_filter = async_graphql_warp::graphql(schema.clone()).and_then(
         |(schema, request): (MySchema, async_graphql::Request)| async move {
@@ -28,12 +25,15 @@ pub async fn serve() {
         .and(warp::post().and(graphql_filter.clone()))
         .or(warp::get().and(playground));
 
-    let cors = warp::cors()
-        .allow_any_origin()
+    let origins = &config.api.cors_allowed_origins;
+    let cors = if origins.iter().any(|origin| origin == "*") {
+        warp::cors().allow_any_origin()
+    } else {
+        warp::cors().allow_origins(origins.iter().map(String::as_str))
+    };
+    let cors = cors
         .allow_methods(&[Method::GET, Method::POST])
         .allow_headers(vec!["content-type", "authorization"]);
 
-    let host = ([127, 0, 0, 1], 3030);
-
-    warp::serve(routes.with(cors)).run(host).await;
+    warp::serve(routes.with(cors)).run(config.api.listen_address).await;
 }
@@ -0,0 +1,39 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// Exponential backoff: each delay doubles the previous one, up to `max`.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+    jitter: bool,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Backoff { initial, max, current: initial, jitter: false }
+    }
+
+    /// Randomizes each delay between half and all of its nominal value, so that clients
+    /// failing at the same moment do not all retry at the same moment.
+    pub fn with_jitter(mut self) -> Self {
+        self.jitter = true;
+        self
+    }
+
+    /// Returns the delay to wait now and doubles the next one.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        if self.jitter {
+            delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+        } else {
+            delay
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
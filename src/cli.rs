@@ -0,0 +1,136 @@
+use clap::{Args, Parser, Subcommand};
+use openrank_relayer::config::Config;
+use openrank_relayer::reindex::{ReindexMode, SeqRange};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Settings are layered: config file, then environment, then these flags.
+#[derive(Debug, Parser)]
+#[command(name = "openrank-relayer", about = "OpenRank - Relayer", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Config file to load instead of the one in the user config directory.
+    #[arg(long, env = "OPENRANK_RELAYER_CONFIG", global = true)]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the indexer and the GraphQL API (default).
+    Run,
+    /// Run only the indexer.
+    IndexOnly,
+    /// Run only the GraphQL API.
+    ServeOnly,
+    /// Rebuild indexed data, then run the indexer and the GraphQL API.
+    Reindex(ReindexArgs),
+    /// Print the indexer cursor against the sequencer head.
+    Status,
+    /// Compare indexed jobs with the sequencer, and optionally repair them.
+    Verify(VerifyArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigOverrides {
+    /// Sequencer JSON-RPC endpoint; repeat for several.
+    #[arg(long = "rpc-url", global = true)]
+    pub rpc_urls: Vec<String>,
+    /// Postgres connection URL.
+    #[arg(long, global = true)]
+    pub database_url: Option<String>,
+    /// Maximum number of database connections, shared by the indexer and the API.
+    #[arg(long, global = true)]
+    pub db_pool_size: Option<u32>,
+    /// Address the GraphQL API listens on.
+    #[arg(long, global = true)]
+    pub bind: Option<SocketAddr>,
+    /// Origin allowed by CORS; repeat for several, `*` allows any.
+    #[arg(long = "cors-origin", global = true)]
+    pub cors_origins: Vec<String>,
+    /// Seconds between indexing passes.
+    #[arg(long, global = true)]
+    pub poll_interval: Option<u64>,
+    /// Maximum number of jobs indexed in one pass.
+    #[arg(long, global = true)]
+    pub batch_size: Option<u64>,
+    /// Jobs fetched ahead and job fetching calls in flight.
+    #[arg(long, global = true)]
+    pub concurrency: Option<usize>,
+    /// Score entries requested per `sequencer_get_results` call.
+    #[arg(long, global = true)]
+    pub scores_page_size: Option<u64>,
+    /// Jobs below the cursor re-polled for new verifications.
+    #[arg(long, global = true)]
+    pub refresh_window: Option<u64>,
+}
+
+impl ConfigOverrides {
+    pub fn apply(&self, config: &mut Config) {
+        if !self.rpc_urls.is_empty() {
+            config.rpc.endpoints = self.rpc_urls.clone();
+        }
+        if let Some(url) = &self.database_url {
+            config.database.url = url.clone();
+        }
+        if let Some(pool_size) = self.db_pool_size {
+            config.database.pool_size = pool_size;
+        }
+        if let Some(bind) = self.bind {
+            config.api.listen_address = bind;
+        }
+        if !self.cors_origins.is_empty() {
+            config.api.cors_allowed_origins = self.cors_origins.clone();
+        }
+        if let Some(interval) = self.poll_interval {
+            config.indexer.poll_interval_secs = interval;
+        }
+        if let Some(batch_size) = self.batch_size {
+            config.indexer.batch_size = batch_size;
+        }
+        if let Some(concurrency) = self.concurrency {
+            config.indexer.concurrency = concurrency;
+        }
+        if let Some(page_size) = self.scores_page_size {
+            config.indexer.scores_page_size = page_size;
+        }
+        if let Some(window) = self.refresh_window {
+            config.indexer.refresh_window = window;
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ReindexArgs {
+    /// Delete jobs from this seq number onwards and resync them.
+    #[arg(long, conflicts_with = "range")]
+    pub from: Option<u64>,
+    /// Re-fetch the jobs in `<a>..<b>` (end exclusive), keeping the cursor in place.
+    #[arg(long, value_name = "A..B")]
+    pub range: Option<SeqRange>,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Compare every job in `<a>..<b>` (end exclusive). Without it, look for a network
+    /// reset instead: the first job from which everything stored differs.
+    #[arg(long, value_name = "A..B")]
+    pub range: Option<SeqRange>,
+    /// Re-fetch or delete the jobs that differ, or rebuild from the reset point.
+    #[arg(long)]
+    pub repair: bool,
+}
+
+impl ReindexArgs {
+    pub fn mode(&self) -> ReindexMode {
+        match (self.from, self.range) {
+            (Some(seq), _) => ReindexMode::From(seq),
+            (None, Some(range)) => ReindexMode::Range(range.start, range.end),
+            (None, None) => ReindexMode::Full,
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Default configuration, written to the user config directory on first start.
+const DEFAULT_CONFIG: &str = include_str!("../config.toml");
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("failed to access config file {path}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("failed to parse config file {path}: {source}")]
+    Parse { path: PathBuf, source: toml::de::Error },
+    #[error("invalid value for {name}: {message}")]
+    Invalid { name: &'static str, message: String },
+    #[error("no config directory could be determined for this user")]
+    NoConfigDir,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub rpc: RpcConfig,
+    pub database: DatabaseConfig,
+    pub api: ApiConfig,
+    pub indexer: IndexerConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcConfig {
+    pub endpoints: Vec<String>,
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Time allowed for a whole request, response body included.
+    #[serde(default = "default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+    /// Retries of a call that failed transiently (timeout, connection reset, 5xx, 429).
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Most calls sent to the sequencer in one JSON-RPC batch.
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+    /// Fetch each compute result from two endpoints and only index it when they agree.
+    #[serde(default)]
+    pub cross_check: bool,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    5
+}
+
+fn default_request_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_retries() -> u32 {
+    5
+}
+
+fn default_max_batch_size() -> usize {
+    50
+}
+
+impl RpcConfig {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub pool_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    pub listen_address: SocketAddr,
+    pub cors_allowed_origins: Vec<String>,
+}
+
+impl ApiConfig {
+    /// Checks the settings the API server needs, which the other commands do not.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(origin) = self.cors_allowed_origins.iter().find(|o| !is_valid_origin(o)) {
+            return Err(ConfigError::Invalid {
+                name: "api.cors_allowed_origins",
+                message: format!("`{}` is not `*` or a `scheme://host[:port]` origin", origin),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexerConfig {
+    pub poll_interval_secs: u64,
+    pub batch_size: u64,
+    /// Jobs fetched ahead of the one being committed, and job fetching calls in flight.
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Score entries requested per `sequencer_get_results` call.
+    #[serde(default = "default_scores_page_size")]
+    pub scores_page_size: u64,
+    /// Jobs below the cursor re-polled for new verifications until they have a final status.
+    #[serde(default = "default_refresh_window")]
+    pub refresh_window: u64,
+}
+
+fn default_concurrency() -> usize {
+    8
+}
+
+fn default_scores_page_size() -> u64 {
+    1000
+}
+
+fn default_refresh_window() -> u64 {
+    100
+}
+
+impl IndexerConfig {
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_secs(self.poll_interval_secs)
+    }
+}
+
+impl Config {
+    /// Loads the config file at `path`, or the one in the user config directory when no
+    /// path is given, creating it from the defaults if it does not exist yet.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let dirs = ProjectDirs::from("", "", "openrank-relayer")
+                    .ok_or(ConfigError::NoConfigDir)?;
+                let path = dirs.config_dir().join("config.toml");
+                if !path.exists() {
+                    log::info!("Creating default config file at {}", path.display());
+                    let io_err = |source| ConfigError::Io { path: path.clone(), source };
+                    fs::create_dir_all(dirs.config_dir()).map_err(io_err)?;
+                    fs::write(&path, DEFAULT_CONFIG).map_err(io_err)?;
+                }
+                path
+            },
+        };
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|source| ConfigError::Io { path: path.clone(), source })?;
+        toml::from_str(&contents).map_err(|source| ConfigError::Parse { path, source })
+    }
+
+    /// Overrides settings from the environment:
+    /// - `PROTOCOL_RPC_URL`: comma-separated sequencer endpoints
+    /// - `RPC_CONNECT_TIMEOUT_SECONDS`, `RPC_REQUEST_TIMEOUT_SECONDS`, `RPC_MAX_RETRIES`,
+    ///   `RPC_MAX_BATCH_SIZE`, `RPC_CROSS_CHECK`
+    /// - `DATABASE_URL`, or `DB_HOST`, `DB_USER`, `DB_PASSWORD` and `DB_NAME`
+    /// - `DB_POOL_SIZE`, `BIND_ADDRESS`, `CORS_ALLOWED_ORIGINS` (comma-separated)
+    /// - `POLL_INTERVAL_SECONDS`, `BATCH_SIZE`, `FETCH_CONCURRENCY`, `SCORES_PAGE_SIZE`,
+    ///   `REFRESH_WINDOW`
+    pub fn apply_env(&mut self) -> Result<(), ConfigError> {
+        if let Some(urls) = var("PROTOCOL_RPC_URL") {
+            self.rpc.endpoints = split_list(&urls);
+        }
+        if let Some(timeout) = var("RPC_CONNECT_TIMEOUT_SECONDS") {
+            self.rpc.connect_timeout_secs = parse("RPC_CONNECT_TIMEOUT_SECONDS", &timeout)?;
+        }
+        if let Some(timeout) = var("RPC_REQUEST_TIMEOUT_SECONDS") {
+            self.rpc.request_timeout_secs = parse("RPC_REQUEST_TIMEOUT_SECONDS", &timeout)?;
+        }
+        if let Some(retries) = var("RPC_MAX_RETRIES") {
+            self.rpc.max_retries = parse("RPC_MAX_RETRIES", &retries)?;
+        }
+        if let Some(batch_size) = var("RPC_MAX_BATCH_SIZE") {
+            self.rpc.max_batch_size = parse("RPC_MAX_BATCH_SIZE", &batch_size)?;
+        }
+        if let Some(cross_check) = var("RPC_CROSS_CHECK") {
+            self.rpc.cross_check = parse("RPC_CROSS_CHECK", &cross_check)?;
+        }
+
+        if let Some(url) = var("DATABASE_URL") {
+            self.database.url = url;
+        } else if let (Some(host), Some(user), Some(name)) =
+            (var("DB_HOST"), var("DB_USER"), var("DB_NAME"))
+        {
+            let password = var("DB_PASSWORD").unwrap_or_default();
+            self.database.url = format!(
+                "postgres://{}:{}@{}/{}",
+                percent_encode(&user),
+                percent_encode(&password),
+                host,
+                percent_encode(&name)
+            );
+        }
+        if let Some(pool_size) = var("DB_POOL_SIZE") {
+            self.database.pool_size = parse("DB_POOL_SIZE", &pool_size)?;
+        }
+
+        if let Some(address) = var("BIND_ADDRESS") {
+            self.api.listen_address = parse("BIND_ADDRESS", &address)?;
+        }
+        if let Some(origins) = var("CORS_ALLOWED_ORIGINS") {
+            self.api.cors_allowed_origins = split_list(&origins);
+        }
+
+        if let Some(interval) = var("POLL_INTERVAL_SECONDS") {
+            self.indexer.poll_interval_secs = parse("POLL_INTERVAL_SECONDS", &interval)?;
+        }
+        if let Some(batch_size) = var("BATCH_SIZE") {
+            self.indexer.batch_size = parse("BATCH_SIZE", &batch_size)?;
+        }
+        if let Some(concurrency) = var("FETCH_CONCURRENCY") {
+            self.indexer.concurrency = parse("FETCH_CONCURRENCY", &concurrency)?;
+        }
+        if let Some(page_size) = var("SCORES_PAGE_SIZE") {
+            self.indexer.scores_page_size = parse("SCORES_PAGE_SIZE", &page_size)?;
+        }
+        if let Some(window) = var("REFRESH_WINDOW") {
+            self.indexer.refresh_window = parse("REFRESH_WINDOW", &window)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the settings that have no usable default.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.rpc.endpoints.is_empty() {
+            return Err(ConfigError::Invalid {
+                name: "rpc.endpoints",
+                message: "at least one sequencer endpoint is required".to_string(),
+            });
+        }
+        if self.rpc.cross_check && self.rpc.endpoints.len() < 2 {
+            return Err(ConfigError::Invalid {
+                name: "rpc.cross_check",
+                message: "cross-checking needs at least two sequencer endpoints".to_string(),
+            });
+        }
+        if self.rpc.connect_timeout_secs == 0 {
+            return Err(ConfigError::Invalid {
+                name: "rpc.connect_timeout_secs",
+                message: "must be greater than zero".to_string(),
+            });
+        }
+        if self.rpc.request_timeout_secs == 0 {
+            return Err(ConfigError::Invalid {
+                name: "rpc.request_timeout_secs",
+                message: "must be greater than zero".to_string(),
+            });
+        }
+        if self.rpc.max_batch_size == 0 {
+            return Err(ConfigError::Invalid {
+                name: "rpc.max_batch_size",
+                message: "must be greater than zero".to_string(),
+            });
+        }
+        self.api.validate()?;
+        if self.indexer.poll_interval_secs == 0 {
+            return Err(ConfigError::Invalid {
+                name: "indexer.poll_interval_secs",
+                message: "must be greater than zero".to_string(),
+            });
+        }
+        if self.indexer.batch_size == 0 {
+            return Err(ConfigError::Invalid {
+                name: "indexer.batch_size",
+                message: "must be greater than zero".to_string(),
+            });
+        }
+        if self.indexer.concurrency == 0 {
+            return Err(ConfigError::Invalid {
+                name: "indexer.concurrency",
+                message: "must be greater than zero".to_string(),
+            });
+        }
+        if self.indexer.scores_page_size == 0 {
+            return Err(ConfigError::Invalid {
+                name: "indexer.scores_page_size",
+                message: "must be greater than zero".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Reads an environment variable, treating an empty value as unset.
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Whether `origin` is `*` or a bare `scheme://host[:port]`, the only forms the CORS filter
+/// accepts.
+fn is_valid_origin(origin: &str) -> bool {
+    if origin == "*" {
+        return true;
+    }
+    match reqwest::Url::parse(origin) {
+        Ok(url) => url.origin().ascii_serialization().eq_ignore_ascii_case(origin),
+        Err(_) => false,
+    }
+}
+
+/// Escapes everything but unreserved characters, for use in a connection URL.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            },
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+fn parse<T: std::str::FromStr>(name: &'static str, value: &str) -> Result<T, ConfigError>
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse().map_err(|e: T::Err| ConfigError::Invalid { name, message: e.to_string() })
+}
@@ -0,0 +1,84 @@
+use crate::protocol_client::RpcError;
+use std::fmt;
+use thiserror::Error;
+
+/// Identifies the job, and optionally the transaction, an error occurred on.
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    pub seq_number: u64,
+    pub tx_hash: Option<String>,
+}
+
+impl JobContext {
+    pub fn job(seq_number: u64) -> Self {
+        JobContext { seq_number, tx_hash: None }
+    }
+
+    pub fn tx(seq_number: u64, tx_hash: &str) -> Self {
+        JobContext { seq_number, tx_hash: Some(tx_hash.to_string()) }
+    }
+}
+
+impl fmt::Display for JobContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "job {}", self.seq_number)?;
+        if let Some(tx_hash) = &self.tx_hash {
+            write!(f, ", tx {}", tx_hash)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RelayerError {
+    #[error("{context}: rpc call failed: {source}")]
+    Rpc { context: JobContext, source: RpcError },
+    #[error("{context}: failed to decode: {source}")]
+    Decode { context: JobContext, source: serde_json::Error },
+    #[error("database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("{context}: missing field `{field}`")]
+    MissingField { context: JobContext, field: &'static str },
+    #[error("{context}: value of `{field}` is out of range")]
+    OutOfRange { context: JobContext, field: &'static str },
+    #[error("{kind} updates: rpc call failed: {source}")]
+    UpdatesRpc { kind: &'static str, source: RpcError },
+    #[error("{kind} updates: failed to decode: {source}")]
+    UpdatesDecode { kind: &'static str, source: serde_json::Error },
+}
+
+impl RelayerError {
+    /// Attaches job context to an error returned by the RPC client, keeping decode
+    /// failures apart from transport failures.
+    pub fn rpc(context: JobContext, source: RpcError) -> Self {
+        match source {
+            RpcError::Decode(source) => RelayerError::Decode { context, source },
+            source => RelayerError::Rpc { context, source },
+        }
+    }
+
+    pub fn decode(context: JobContext, source: serde_json::Error) -> Self {
+        RelayerError::Decode { context, source }
+    }
+
+    /// Error of the RPC client while fetching TrustUpdate or SeedUpdate transactions, which
+    /// do not belong to a job.
+    pub fn updates_rpc(kind: &'static str, source: RpcError) -> Self {
+        match source {
+            RpcError::Decode(source) => RelayerError::UpdatesDecode { kind, source },
+            source => RelayerError::UpdatesRpc { kind, source },
+        }
+    }
+
+    pub fn updates_decode(kind: &'static str, source: serde_json::Error) -> Self {
+        RelayerError::UpdatesDecode { kind, source }
+    }
+
+    pub fn missing_field(context: JobContext, field: &'static str) -> Self {
+        RelayerError::MissingField { context, field }
+    }
+
+    pub fn out_of_range(context: JobContext, field: &'static str) -> Self {
+        RelayerError::OutOfRange { context, field }
+    }
+}
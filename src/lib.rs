@@ -1,183 +1,758 @@
-use crate::protocol_client::RpcClient;
-use async_recursion::async_recursion;
-use log::info;
-use std::env;
+use crate::backoff::Backoff;
+use crate::config::{Config, IndexerConfig};
+use crate::error::{JobContext, RelayerError};
+use crate::protocol_client::{EndpointHealth, RpcClient};
+use crate::reindex::{ReindexMode, SeqRange};
+use crate::storage::{self, Storage};
+use crate::types::{
+    to_hex_string, ComputeResult, FetchedTx, IndexedJob, IndexedScoreEntry, IndexedSeedEntry,
+    IndexedTrustEdge, IndexedTx, IndexedVerification, JobChange, TxKind,
+};
+use chrono::{DateTime, Utc};
+use futures::{future, stream, StreamExt};
+use log::{error, info, warn};
+use openrank_common::tx::compute::{Assignment, Scores, Verification};
+use openrank_common::tx::Body;
+use std::future::Future;
+use std::pin::pin;
+use tokio::sync::Semaphore;
 use tokio::time::Duration;
 
-mod postgres;
+mod backoff;
+pub mod config;
+mod error;
 mod protocol_client;
-mod types;
+pub mod reindex;
+pub mod storage;
+pub mod types;
 
-const INTERVAL_SECONDS: u64 = 10;
+const MAX_BACKOFF_SECONDS: u64 = 300;
+
+type ProcessResult = Result<(), RelayerError>;
+
+/// Position of the indexer relative to the sequencer.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexStatus {
+    /// Next job seq number the indexer will fetch.
+    pub cursor: u64,
+    /// First job seq number the sequencer has no compute result for.
+    pub head: u64,
+}
+
+/// Outcome of comparing stored jobs with the sequencer.
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// Number of jobs compared.
+    pub checked: u64,
+    /// Jobs stored with a different commitment, request or verifications than the
+    /// sequencer now returns.
+    pub mismatched: Vec<u64>,
+    /// Jobs that only got new verifications since they were stored, and need a refresh.
+    pub stale: Vec<u64>,
+    /// Jobs stored that the sequencer no longer has.
+    pub missing: Vec<u64>,
+    /// Jobs the sequencer has below the cursor that are not stored.
+    pub unindexed: Vec<u64>,
+}
+
+impl VerifyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.unindexed.is_empty()
+    }
+
+    pub fn is_up_to_date(&self) -> bool {
+        self.is_consistent() && self.stale.is_empty()
+    }
+}
 
 pub struct SQLRelayer {
-    target_db: postgres::SQLDatabase,
+    target_db: Storage,
     protocol_client: RpcClient,
+    /// Bounds the number of job fetching calls in flight, compute results and
+    /// `sequencer_get_tx` batches alike.
+    rpc_permits: Semaphore,
+    indexer: IndexerConfig,
 }
 
 impl SQLRelayer {
-    pub async fn init(is_reindex: bool) -> Self {
-        let target_db = postgres::SQLDatabase::connect().await.expect("Connect to Postgres db");
+    /// Connects to the database and the sequencer without touching the schema, for
+    /// read-only commands.
+    pub async fn connect(config: &Config) -> Self {
+        let target_db = Storage::connect(&config.database).await.expect("Connect to Postgres db");
+        let protocol_client = RpcClient::new(&config.rpc).expect("Failed to build RPC client");
+
+        SQLRelayer {
+            target_db,
+            protocol_client,
+            rpc_permits: Semaphore::new(config.indexer.concurrency),
+            indexer: config.indexer.clone(),
+        }
+    }
 
-        if is_reindex {
+    pub async fn init(config: &Config, reindex: Option<ReindexMode>) -> Self {
+        let mut relayer = Self::connect(config).await;
+
+        if reindex == Some(ReindexMode::Full) {
             log::info!("Reindexing: dropping tables.");
-            target_db.drop_tables().await.unwrap();
+            relayer.target_db.drop_tables().await.unwrap();
+        }
+
+        relayer.target_db.migrate().await.unwrap();
+
+        if let Some(mode) = reindex {
+            relayer.reindex(mode).await.expect("Failed to reindex");
+        }
+
+        relayer
+    }
+
+    /// Rebuilds part of the indexed data. A full reindex has already dropped the tables by
+    /// the time this runs, so only the partial modes have work to do here.
+    async fn reindex(&mut self, mode: ReindexMode) -> Result<(), RelayerError> {
+        log::info!("Reindexing: {}.", mode);
+        match mode {
+            ReindexMode::Full => {},
+            ReindexMode::From(seq) => {
+                self.target_db.delete_jobs(seq, None, Some("jobs")).await?;
+            },
+            ReindexMode::Range(start, end) => {
+                let cursor = self.target_db.load_last_processed_key("jobs").await?.unwrap_or(0);
+
+                // Jobs at or past the cursor have not been indexed yet; the indexer picks
+                // them up as usual.
+                for seq in start..end.min(cursor) {
+                    self.refetch_job(seq).await?;
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Replaces a stored job with a fresh copy from the sequencer, or deletes it if the
+    /// sequencer no longer has it.
+    async fn refetch_job(&self, seq: u64) -> ProcessResult {
+        match self.fetch_compute_result(seq).await? {
+            Some(result) => {
+                let job = self.fetch_job(seq, &result).await?;
+                self.target_db.replace_job(&job).await?;
+            },
+            None => {
+                log::warn!("Job {} is no longer available, deleting it.", seq);
+                self.target_db.delete_jobs(seq, Some(seq + 1), None).await?;
+            },
+        }
+        Ok(())
+    }
+
+    /// Rewrites a stored job that only got new verifications, keeping its scores.
+    async fn refresh_job(&self, seq: u64, result: &ComputeResult) -> ProcessResult {
+        let job = self.fetch_job(seq, result).await?;
+        self.target_db.refresh_job(&job).await?;
+        Ok(())
+    }
+
+    /// Compares the stored transaction hashes of the jobs in `range` (clamped to the
+    /// cursor) with the compute results the sequencer returns now. With `repair`, stale
+    /// jobs are refreshed, and every other job that differs is re-fetched, or deleted if
+    /// the sequencer no longer has it.
+    pub async fn verify(
+        &self, range: SeqRange, repair: bool,
+    ) -> Result<VerifyReport, RelayerError> {
+        let cursor = self.target_db.load_last_processed_key("jobs").await?.unwrap_or(0);
+
+        let mut report = VerifyReport::default();
+        for seq in range.start..range.end.min(cursor) {
+            let stored = self.target_db.job_transaction_hashes(seq).await?;
+            let current = self.fetch_compute_result(seq).await?;
+            report.checked += 1;
+
+            match (stored, current) {
+                (Some(stored), Some(current)) => match current.compare(&stored) {
+                    JobChange::Unchanged => continue,
+                    JobChange::NewVerifications => {
+                        info!("Job {} has new verifications.", seq);
+                        report.stale.push(seq);
+                        if repair {
+                            self.refresh_job(seq, &current).await?;
+                        }
+                        continue;
+                    },
+                    JobChange::Diverged => {
+                        warn!("Job {} differs from the sequencer.", seq);
+                        report.mismatched.push(seq);
+                    },
+                },
+                (Some(_), None) => {
+                    warn!("Job {} is no longer on the sequencer.", seq);
+                    report.missing.push(seq);
+                },
+                (None, Some(_)) => {
+                    warn!("Job {} is not indexed.", seq);
+                    report.unindexed.push(seq);
+                },
+                (None, None) => continue,
+            }
+            if repair {
+                self.refetch_job(seq).await?;
+            }
         }
 
-        target_db.init().await.unwrap();
+        Ok(report)
+    }
+
+    /// Looks for a network reset: a point from which every stored job differs from what
+    /// the sequencer returns. Returns the first differing seq number, found by bisection,
+    /// or `None` when the latest stored job still matches. With `repair`, every job from
+    /// there on is deleted and the cursor rewound, so the indexer rebuilds them.
+    pub async fn detect_reset(&self, repair: bool) -> Result<Option<u64>, RelayerError> {
+        let cursor = self.target_db.load_last_processed_key("jobs").await?.unwrap_or(0);
+        if cursor == 0 || self.job_matches(cursor - 1).await? {
+            return Ok(None);
+        }
 
-        let url = env::var("PROTOCOL_RPC_URL").expect("PROTOCOL_RPC_URL must be set");
-        let protocol_client = RpcClient::new(&url);
+        // `high` is known to differ; find the lowest seq that does.
+        let (mut low, mut high) = (0, cursor - 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.job_matches(mid).await? {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
 
-        SQLRelayer { target_db, protocol_client }
+        warn!(
+            "Stored jobs differ from the sequencer from job {} on.",
+            high
+        );
+        if repair {
+            self.target_db.delete_jobs(high, None, Some("jobs")).await?;
+        }
+        Ok(Some(high))
     }
 
-    async fn save_last_processed_key(&self, db_path: &str, last_processed_key: usize) {
-        self.target_db
-            .save_last_processed_key(db_path, last_processed_key as i32).await
-            .expect("Failed to save last processed key");
+    /// Whether stored job `seq` agrees with the sequencer, new verifications aside.
+    async fn job_matches(&self, seq: u64) -> Result<bool, RelayerError> {
+        let stored = self.target_db.job_transaction_hashes(seq).await?;
+        let current = self.fetch_compute_result(seq).await?;
+        Ok(match (stored, current) {
+            (Some(stored), Some(current)) => current.compare(&stored) != JobChange::Diverged,
+            (stored, current) => stored.is_none() && current.is_none(),
+        })
     }
 
-    async fn index(&mut self) {
-        let last_count = self.target_db
-            .load_last_processed_key("jobs").await
-            .expect("Failed to load last processed key")
-            .unwrap_or(0);
+    /// Compares the stored cursor with the sequencer head. The head is found by probing
+    /// exponentially past the cursor and then bisecting, so a large gap costs only a
+    /// logarithmic number of RPC calls.
+    pub async fn status(&self) -> Result<IndexStatus, RelayerError> {
+        let cursor = self.target_db.load_last_processed_key("jobs").await?.unwrap_or(0);
+
+        let mut low = cursor;
+        let mut step = 1;
+        while self.fetch_compute_result(low).await?.is_some() {
+            low += step;
+            step *= 2;
+        }
+
+        // `low` has no result; the last known present seq is `low - step / 2` (or none).
+        let mut present = if step > 1 { Some(low - step / 2) } else { None };
+        while let Some(p) = present {
+            if p + 1 >= low {
+                break;
+            }
+            let mid = p + (low - p) / 2;
+            if self.fetch_compute_result(mid).await?.is_some() {
+                present = Some(mid);
+            } else {
+                low = mid;
+            }
+        }
+
+        let head = present.map_or(cursor, |p| p + 1);
+        Ok(IndexStatus { cursor, head })
+    }
+
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.protocol_client.endpoint_health()
+    }
+
+    /// Fetches the compute result for `seq`, or `None` when the sequencer has none yet.
+    /// Any other error response fails the pass instead of being taken for the end of the
+    /// stream.
+    async fn fetch_compute_result(&self, seq: u64) -> Result<Option<ComputeResult>, RelayerError> {
+        let _permit = self.rpc_permits.acquire().await.expect("semaphore is never closed");
+        self.protocol_client
+            .sequencer_get_compute_result(seq)
+            .await
+            .map_err(|e| RelayerError::rpc(JobContext::job(seq), e))
+    }
+
+    /// Indexes up to `batch_size` new jobs, returning how many were indexed.
+    async fn index(&self) -> Result<u64, RelayerError> {
+        let last_count = self.target_db.load_last_processed_key("jobs").await?.unwrap_or(0);
 
         let mut current_count = last_count;
+        let end = current_count + self.indexer.batch_size;
 
         log::info!("Indexing db, last_count: {:?}", last_count);
-        loop {
-            let compute_result = self.protocol_client
-                .sequencer_get_compute_result(current_count.try_into().unwrap()).await
-                .unwrap();
 
-            if compute_result.get("error").is_some() {
-                // error!("{:?}", compute_result);
-                break;
+        // Up to `concurrency` jobs are fetched ahead; `buffered` yields them in seq order,
+        // so they are still committed one after another.
+        let mut jobs = pin!(stream::iter(current_count..end)
+            .map(|seq| async move {
+                match self.fetch_compute_result(seq).await? {
+                    Some(result) => self.fetch_job(seq, &result).await.map(Some),
+                    None => Ok(None),
+                }
+            })
+            .buffered(self.indexer.concurrency));
+
+        while let Some(job) = jobs.next().await {
+            let job = match job? {
+                Some(job) => job,
+                None => break,
+            };
+
+            current_count += 1;
+            self.commit_job(&job, current_count).await?;
+        }
+
+        Ok(current_count - last_count)
+    }
+
+    /// Re-polls the jobs of the refresh window below the cursor that are neither verified
+    /// nor rejected, rewriting those that got new verifications. Returns how many were
+    /// rewritten.
+    async fn refresh_pending_jobs(&self) -> Result<u64, RelayerError> {
+        let cursor = self.target_db.load_last_processed_key("jobs").await?.unwrap_or(0);
+        let from = cursor.saturating_sub(self.indexer.refresh_window);
+
+        let mut refreshed = 0;
+        for (seq, stored) in self.target_db.pending_jobs(from).await? {
+            let Some(result) = self.fetch_compute_result(seq).await? else {
+                continue;
+            };
+            match result.compare(&stored) {
+                JobChange::Unchanged => {},
+                JobChange::NewVerifications => {
+                    self.refresh_job(seq, &result).await?;
+                    refreshed += 1;
+                },
+                JobChange::Diverged => {
+                    warn!("Job {} differs from the sequencer, run verify.", seq);
+                },
             }
-            let result = compute_result.get("result").unwrap();
-
-            let compute_commitment_tx_hash = result
-                .get("compute_commitment_tx_hash")
-                .and_then(|v| v.as_str())
-                .expect("must be a string")
-                .to_string();
-
-            let compute_request_tx_hash = result
-                .get("compute_request_tx_hash")
-                .and_then(|v| v.as_str())
-                .expect("must be a string")
-                .to_string();
-
-            let mut hashes = vec![
-                compute_commitment_tx_hash.clone(),
-                compute_request_tx_hash.clone()
-            ];
-
-            if
-                let Some(verification_hashes) = result
-                    .get("compute_verification_tx_hashes")
-                    .and_then(|v| v.as_array())
-            {
-                hashes.extend(
-                    verification_hashes.iter().filter_map(|v| v.as_str().map(|s| s.to_string()))
-                );
+        }
+
+        Ok(refreshed)
+    }
+
+    /// Fills in the timestamps of up to `batch_size` jobs stored without one, which migration
+    /// 0006 cleared. Returns how many were filled in.
+    async fn backfill_timestamps(&self) -> Result<u64, RelayerError> {
+        let mut filled = 0;
+        for seq in self.target_db.jobs_without_timestamp(self.indexer.batch_size).await? {
+            let Some(result) = self.fetch_compute_result(seq).await? else {
+                warn!("Job {} is no longer on the sequencer, run verify.", seq);
+                continue;
+            };
+            let timestamp = job_timestamp(seq, &result)?;
+            self.target_db.set_job_timestamp(seq, timestamp).await?;
+            filled += 1;
+        }
+
+        Ok(filled)
+    }
+
+    /// Fetches the final scores of up to `batch_size` indexed jobs that do not have them all
+    /// yet, returning how many jobs were completed.
+    async fn index_scores(&self) -> Result<u64, RelayerError> {
+        let jobs_cursor = self.target_db.load_last_processed_key("jobs").await?.unwrap_or(0);
+        let scores_cursor = self.target_db.load_last_processed_key("scores").await?.unwrap_or(0);
+
+        let end = jobs_cursor.min(scores_cursor + self.indexer.batch_size);
+        for seq in scores_cursor..end {
+            self.index_job_scores(seq).await?;
+        }
+
+        Ok(end.saturating_sub(scores_cursor))
+    }
+
+    /// Pages through the final scores of job `seq`. Every page is committed on its own
+    /// together with its positions, so an interrupted job resumes after the last stored
+    /// entry instead of starting over.
+    async fn index_job_scores(&self, seq: u64) -> ProcessResult {
+        let context = JobContext::job(seq);
+        let page_size = self.indexer.scores_page_size;
+
+        let request_tx_hash = match self.target_db.compute_request_hash(seq).await? {
+            Some(hash) => hash,
+            // The job was deleted because the sequencer no longer has it; nothing to fetch.
+            None => {
+                self.target_db.commit_scores_page(seq, 0, &[], true).await?;
+                return Ok(());
+            },
+        };
+
+        let mut start = self.target_db.next_score_position(seq).await?;
+        loop {
+            let (_, entries) = self
+                .protocol_client
+                .sequencer_get_results(&request_tx_hash, start, page_size)
+                .await
+                .map_err(|e| RelayerError::rpc(context.clone(), e))?
+                .ok_or_else(|| RelayerError::missing_field(context.clone(), "result"))?;
+
+            let complete = (entries.len() as u64) < page_size;
+            self.target_db.commit_scores_page(seq, start, &entries, complete).await?;
+            if complete {
+                return Ok(());
             }
+            start += entries.len() as u64;
+        }
+    }
 
-            let seq_number = result
-                .get("seq_number")
-                .and_then(|v| v.as_i64())
-                .expect("seq_number should be an integer") as i32;
-
-            let timestamp = result
-                .get("timestamp")
-                .and_then(|v| v.as_i64())
-                .expect("seq_number should be an integer") as i32;
-
-            self.target_db.insert_job(seq_number, timestamp, hashes.clone()).await.unwrap();
-
-            let mut transactions = vec![
-                ("compute_commitment", compute_commitment_tx_hash.clone()),
-                ("compute_request", compute_request_tx_hash.clone())
-            ];
-
-            let verification_transactions: Vec<(&str, String)> = result
-                .get("compute_verification_tx_hashes")
-                .and_then(|v| v.as_array())
-                .map(|hashes| {
-                    hashes
-                        .iter()
-                        .filter_map(|hash| {
-                            hash.as_str().map(|s| ("compute_verification", s.to_string()))
-                        })
-                        .collect::<Vec<(&str, String)>>()
-                })
-                .unwrap_or_else(Vec::new);
+    /// Indexes the next `batch_size` TrustUpdate transactions into trust graph edges,
+    /// returning how many transactions were indexed.
+    async fn index_trust_updates(&self) -> Result<u64, RelayerError> {
+        let offset = self.target_db.load_last_processed_key("trust_updates").await?.unwrap_or(0);
+        let txs = self
+            .protocol_client
+            .sequencer_get_trust_updates(offset, self.indexer.batch_size)
+            .await
+            .map_err(|e| RelayerError::updates_rpc("trust", e))?
+            .unwrap_or_default();
+        if txs.is_empty() {
+            return Ok(0);
+        }
+
+        let decode = |e: serde_json::Error| RelayerError::updates_decode("trust", e);
+        let mut edges = Vec::new();
+        for tx in &txs {
+            let update = match tx.body() {
+                Body::TrustUpdate(update) => update,
+                _ => return Err(decode(serde::de::Error::custom("expected a TrustUpdate"))),
+            };
+            let tx_hash = to_hex_string(&tx.hash()).map_err(decode)?;
+            let trust_id = to_hex_string(update.trust_id()).map_err(decode)?;
+            edges.extend(update.entries().iter().map(|entry| IndexedTrustEdge {
+                tx_hash: tx_hash.clone(),
+                trust_id: trust_id.clone(),
+                from: entry.from().clone(),
+                to: entry.to().clone(),
+                weight: f64::from(*entry.value()),
+            }));
+        }
+
+        self.target_db.commit_trust_edges(&edges, offset + txs.len() as u64).await?;
+        Ok(txs.len() as u64)
+    }
+
+    /// Indexes the next `batch_size` SeedUpdate transactions into seed entries, returning
+    /// how many transactions were indexed.
+    async fn index_seed_updates(&self) -> Result<u64, RelayerError> {
+        let offset = self.target_db.load_last_processed_key("seed_updates").await?.unwrap_or(0);
+        let txs = self
+            .protocol_client
+            .sequencer_get_seed_updates(offset, self.indexer.batch_size)
+            .await
+            .map_err(|e| RelayerError::updates_rpc("seed", e))?
+            .unwrap_or_default();
+        if txs.is_empty() {
+            return Ok(0);
+        }
 
-            transactions.extend(verification_transactions);
+        let decode = |e: serde_json::Error| RelayerError::updates_decode("seed", e);
+        let mut entries = Vec::new();
+        for tx in &txs {
+            let update = match tx.body() {
+                Body::SeedUpdate(update) => update,
+                _ => return Err(decode(serde::de::Error::custom("expected a SeedUpdate"))),
+            };
+            let tx_hash = to_hex_string(&tx.hash()).map_err(decode)?;
+            let seed_id = to_hex_string(update.seed_id()).map_err(decode)?;
+            entries.extend(update.entries().iter().map(|entry| IndexedSeedEntry {
+                tx_hash: tx_hash.clone(),
+                seed_id: seed_id.clone(),
+                peer_id: entry.id().clone(),
+                value: f64::from(*entry.value()),
+            }));
+        }
 
-            for (tx_type, hash) in transactions {
-                self.process_transaction(current_count.try_into().unwrap(), tx_type, &hash).await;
+        self.target_db.commit_seed_entries(&entries, offset + txs.len() as u64).await?;
+        Ok(txs.len() as u64)
+    }
+
+    /// Commits an already fetched job. If the database connection was lost, the pool
+    /// reconnects and the commit is retried with backoff instead of discarding the job.
+    async fn commit_job(&self, job: &IndexedJob, next_key: u64) -> Result<(), RelayerError> {
+        let mut backoff = Backoff::new(
+            Duration::from_secs(1),
+            Duration::from_secs(MAX_BACKOFF_SECONDS),
+        );
+        loop {
+            match self.target_db.commit_job(job, "jobs", next_key).await {
+                Err(e) if storage::is_connection_error(&e) => {
+                    let delay = backoff.next_delay();
+                    warn!(
+                        "Database connection lost while committing job {}, retrying in {:?}: {}",
+                        job.seq_number, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                },
+                result => return result.map_err(RelayerError::from),
             }
+        }
+    }
 
-            current_count += 1;
-            if last_count < current_count {
-                self.save_last_processed_key("jobs", current_count).await;
+    /// Fetches every transaction belonging to a compute result, without writing anything.
+    /// Transactions are fetched in concurrent batches, one level of links at a time.
+    async fn fetch_job(
+        &self, current_count: u64, result: &ComputeResult,
+    ) -> Result<IndexedJob, RelayerError> {
+        let seq_number = result.seq_number.ok_or_else(|| {
+            RelayerError::missing_field(JobContext::job(current_count), "seq_number")
+        })?;
+        let timestamp = job_timestamp(current_count, result)?;
+
+        let mut job = IndexedJob {
+            seq_number,
+            timestamp,
+            transaction_hashes: result.transaction_hashes(),
+            transactions: Vec::new(),
+            score_entries: Vec::new(),
+            assigned_verifiers: Vec::new(),
+            verifications: Vec::new(),
+        };
+
+        let mut pending = vec![
+            (
+                TxKind::ComputeCommitment,
+                result.compute_commitment_tx_hash.clone(),
+            ),
+            (
+                TxKind::ComputeRequest,
+                result.compute_request_tx_hash.clone(),
+            ),
+        ];
+        pending.extend(
+            result
+                .compute_verification_tx_hashes
+                .iter()
+                .map(|hash| (TxKind::ComputeVerification, hash.clone())),
+        );
+
+        let mut fetched = Vec::new();
+        while !pending.is_empty() {
+            let batches = pending.chunks(self.protocol_client.max_batch_size());
+            let level: Vec<FetchedTx> = future::try_join_all(
+                batches.map(|batch| self.fetch_transactions(current_count, batch)),
+            )
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+
+            pending = Vec::new();
+            for tx in &level {
+                pending.extend(linked_transactions(current_count, tx)?);
             }
+            fetched.extend(level);
+        }
+
+        for tx in fetched {
+            self.index_transaction(&mut job, current_count, tx)?;
         }
+
+        Ok(job)
     }
 
+    /// Runs the indexer forever. Every stage runs in its own loop, so that a stage that keeps
+    /// failing does not hold back the others: it runs again right away after a full batch,
+    /// after the poll interval otherwise, and with exponential backoff after a failure.
     pub async fn start(&mut self) {
-        let mut interval = tokio::time::interval(Duration::from_secs(INTERVAL_SECONDS));
+        tokio::join!(
+            self.run_stage("jobs", || self.index()),
+            self.run_stage("scores", || self.index_scores()),
+            self.run_stage("trust updates", || self.index_trust_updates()),
+            self.run_stage("seed updates", || self.index_seed_updates()),
+            self.run_stage("pending jobs", || self.refresh_pending_jobs()),
+            self.run_stage("job timestamps", || self.backfill_timestamps()),
+        );
+    }
+
+    async fn run_stage<F, Fut>(&self, name: &str, stage: F)
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<u64, RelayerError>>,
+    {
+        let poll_interval = self.indexer.poll_interval();
+        let mut backoff = Backoff::new(poll_interval, Duration::from_secs(MAX_BACKOFF_SECONDS));
 
         loop {
-            interval.tick().await;
-            info!("Running periodic index check...");
-            self.index().await;
-        }
-    }
-
-    #[async_recursion]
-    async fn process_transaction(&self, seq_id: i32, tx_type: &str, hash: &str) {
-        let res = self.protocol_client
-            .sequencer_get_tx(tx_type, hash).await
-            .expect("Failed to get transaction");
-
-        let body = res.pointer("/result/body").expect("Missing txn body").to_string();
-        let to = res
-            .pointer("/result/to")
-            .and_then(|v| v.as_str())
-            .expect("must be a string")
-            .to_string();
-        let from = res
-            .pointer("/result/from")
-            .and_then(|v| v.as_str())
-            .expect("must be a string")
-            .to_string();
-
-        if tx_type == "compute_commitment" {
-            let assignment_tx_hash = res
-                .pointer("/result/body/ComputeCommitment/assignment_tx_hash")
-                .and_then(|v| v.as_str())
-                .expect("must be a string")
-                .to_string();
-
-            self.process_transaction(seq_id, "compute_assignment", &assignment_tx_hash).await;
-
-            if
-                let Some(scores_tx_hashes) = res
-                    .pointer("/result/body/ComputeCommitment/scores_tx_hashes")
-                    .and_then(|v| v.as_array())
-            {
-                for score_tx_hash in scores_tx_hashes {
-                    if let Some(score_tx_hash_str) = score_tx_hash.as_str() {
-                        self.process_transaction(seq_id, "compute_scores", score_tx_hash_str).await;
+            info!("Running periodic {} index check...", name);
+            match stage().await {
+                // A full batch means the sequencer is likely ahead; keep going right away.
+                Ok(count) if count == self.indexer.batch_size => backoff.reset(),
+                Ok(_) => {
+                    backoff.reset();
+                    tokio::time::sleep(poll_interval).await;
+                },
+                Err(e) => {
+                    let delay = backoff.next_delay();
+                    error!("Indexing {} failed, retrying in {:?}: {}", name, delay, e);
+                    error!("RPC metrics: {}", self.protocol_client.metrics());
+                    for endpoint in self.protocol_client.endpoint_health() {
+                        error!("RPC endpoint {}", endpoint);
                     }
-                }
+                    tokio::time::sleep(delay).await;
+                },
+            }
+        }
+    }
+
+    /// Fetches a batch of transactions in one call. Every entry that failed, or whose body
+    /// is not of the kind it was requested as, is logged, and the first failure is returned.
+    async fn fetch_transactions(
+        &self, seq_id: u64, batch: &[(TxKind, String)],
+    ) -> Result<Vec<FetchedTx>, RelayerError> {
+        let _permit = self.rpc_permits.acquire().await.expect("semaphore is never closed");
+        let results = self
+            .protocol_client
+            .sequencer_get_txs(batch)
+            .await
+            .map_err(|e| RelayerError::rpc(JobContext::job(seq_id), e))?;
+
+        let mut fetched = Vec::with_capacity(batch.len());
+        let mut first_error = None;
+        for ((kind, hash), result) in batch.iter().zip(results) {
+            let context = JobContext::tx(seq_id, hash);
+            let result = result
+                .map_err(|e| RelayerError::rpc(context.clone(), e))
+                .and_then(|tx| {
+                    tx.ok_or_else(|| RelayerError::missing_field(context.clone(), "result"))
+                })
+                .and_then(|tx| match TxKind::of(tx.body()) {
+                    Some(body_kind) if body_kind == *kind => Ok(tx),
+                    _ => Err(unexpected_body(context, *kind)),
+                });
+            match result {
+                Ok(tx) => fetched.push(FetchedTx { kind: *kind, hash: hash.clone(), tx }),
+                Err(e) => {
+                    warn!("Failed to fetch transaction: {}", e);
+                    first_error.get_or_insert(e);
+                },
             }
         }
 
-        let _ = self.target_db.insert_transactions(seq_id, hash, &body, tx_type, &to, &from).await;
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(fetched),
+        }
+    }
+
+    fn index_transaction(
+        &self, job: &mut IndexedJob, seq_id: u64, fetched: FetchedTx,
+    ) -> ProcessResult {
+        let FetchedTx { kind, hash, tx } = fetched;
+        let context = JobContext::tx(seq_id, &hash);
+
+        let body = serde_json::to_value(tx.body())
+            .map_err(|e| RelayerError::decode(context.clone(), e))?;
+        let to = to_hex_string(tx.to()).map_err(|e| RelayerError::decode(context.clone(), e))?;
+        let from =
+            to_hex_string(tx.from()).map_err(|e| RelayerError::decode(context.clone(), e))?;
+
+        match tx.body() {
+            Body::ComputeRequest(_) | Body::ComputeCommitment(_) => {},
+            Body::ComputeAssignment(assignment) => {
+                self.handle_compute_assignment(job, &context, assignment)?
+            },
+            Body::ComputeScores(scores) => self.handle_compute_scores(job, &hash, scores)?,
+            Body::ComputeVerification(verification) => {
+                self.handle_compute_verification(job, &hash, &from, verification)?
+            },
+            _ => return Err(unexpected_body(context, kind)),
+        }
+
+        job.transactions.push(IndexedTx { kind, hash, body, to, from });
+
+        Ok(())
     }
+
+    /// Records the verifiers the job was assigned to, who all have to vote, or be unable to
+    /// change the majority, before the job gets a final status.
+    fn handle_compute_assignment(
+        &self, job: &mut IndexedJob, context: &JobContext, assignment: &Assignment,
+    ) -> ProcessResult {
+        for node in assignment.assigned_verifier_nodes() {
+            let node = to_hex_string(node).map_err(|e| RelayerError::decode(context.clone(), e))?;
+            job.assigned_verifiers.push(node);
+        }
+        Ok(())
+    }
+
+    /// Explodes the score entries of a ComputeScores transaction into rows, so that score
+    /// lookups do not have to scan transaction bodies.
+    fn handle_compute_scores(
+        &self, job: &mut IndexedJob, hash: &str, scores: &Scores,
+    ) -> ProcessResult {
+        job.score_entries.extend(scores.entries().iter().map(|entry| IndexedScoreEntry {
+            tx_hash: hash.to_string(),
+            peer_id: entry.id().clone(),
+            value: f64::from(*entry.value()),
+        }));
+        Ok(())
+    }
+
+    /// Records the verifier's vote towards the job's final status.
+    fn handle_compute_verification(
+        &self, job: &mut IndexedJob, hash: &str, verifier: &str, verification: &Verification,
+    ) -> ProcessResult {
+        job.verifications.push(IndexedVerification {
+            tx_hash: hash.to_string(),
+            verifier: verifier.to_string(),
+            result: *verification.verification_result(),
+        });
+        Ok(())
+    }
+}
+
+/// Time of a job; the sequencer reports milliseconds since the epoch.
+fn job_timestamp(seq_id: u64, result: &ComputeResult) -> Result<DateTime<Utc>, RelayerError> {
+    i64::try_from(result.timestamp)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .ok_or_else(|| RelayerError::out_of_range(JobContext::job(seq_id), "timestamp"))
+}
+
+fn unexpected_body(context: JobContext, kind: TxKind) -> RelayerError {
+    let message = format!("unexpected body for a {} transaction", kind.as_str());
+    RelayerError::decode(context, serde::de::Error::custom(message))
+}
+
+/// Transactions referenced by `fetched` that belong to the same job: a commitment links
+/// to its assignment and to every scores transaction.
+fn linked_transactions(
+    seq_id: u64, fetched: &FetchedTx,
+) -> Result<Vec<(TxKind, String)>, RelayerError> {
+    let commitment = match fetched.tx.body() {
+        Body::ComputeCommitment(commitment) => commitment,
+        _ => return Ok(Vec::new()),
+    };
+    let decode = |e| RelayerError::decode(JobContext::tx(seq_id, &fetched.hash), e);
+
+    let mut linked = vec![(
+        TxKind::ComputeAssignment,
+        to_hex_string(commitment.assignment_tx_hash()).map_err(decode)?,
+    )];
+    for scores_tx_hash in commitment.scores_tx_hashes() {
+        linked.push((
+            TxKind::ComputeScores,
+            to_hex_string(scores_tx_hash).map_err(decode)?,
+        ));
+    }
+
+    Ok(linked)
 }
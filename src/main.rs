@@ -1,30 +1,91 @@
 use api::server::serve;
+use clap::Parser;
+use cli::{Cli, Command};
 use dotenv::dotenv;
+use openrank_relayer::config::Config;
 use openrank_relayer::{self, SQLRelayer};
-use std::env;
 use std::error::Error;
 
 pub mod api;
+mod cli;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     dotenv().ok();
     env_logger::init();
 
-    let args: Vec<String> = env::args().collect();
-    let is_reindex = args.contains(&"reindex".to_string());
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Run);
 
-    // let config_loader = config::Loader::new("openrank-relayer")?;
-    // let config: Config = config_loader.load_or_create(include_str!("../config.toml"))?;
+    let mut config = Config::load(cli.config.as_deref())?;
+    config.apply_env()?;
+    cli.overrides.apply(&mut config);
 
-    let mut relayer = SQLRelayer::init(is_reindex).await;
+    let reindex = match &command {
+        Command::Reindex(args) => Some(args.mode()),
+        _ => None,
+    };
 
-    let serve_job = tokio::spawn(async move { serve().await });
-    let relayer_job = tokio::spawn(async move { relayer.start().await });
-    let (serve_res, relayer_res) = tokio::join!(serve_job, relayer_job);
+    match command {
+        Command::ServeOnly => {
+            config.api.validate()?;
+            serve(config).await
+        },
+        Command::IndexOnly => {
+            config.validate()?;
+            let mut relayer = SQLRelayer::init(&config, None).await;
+            relayer.start().await;
+        },
+        Command::Status => {
+            config.validate()?;
+            let relayer = SQLRelayer::connect(&config).await;
+            let status = relayer.status().await?;
+            println!("cursor: {}", status.cursor);
+            println!("head:   {}", status.head);
+            println!("behind: {}", status.head.saturating_sub(status.cursor));
+            for endpoint in relayer.endpoint_health() {
+                println!("rpc:    {}", endpoint);
+            }
+        },
+        Command::Verify(args) => {
+            config.validate()?;
+            // Only a repair writes, and needs the schema to be current.
+            let relayer = if args.repair {
+                SQLRelayer::init(&config, None).await
+            } else {
+                SQLRelayer::connect(&config).await
+            };
+            match args.range {
+                Some(range) => {
+                    let report = relayer.verify(range, args.repair).await?;
+                    println!("checked:    {}", report.checked);
+                    println!("mismatched: {:?}", report.mismatched);
+                    println!("stale:      {:?}", report.stale);
+                    println!("missing:    {:?}", report.missing);
+                    println!("unindexed:  {:?}", report.unindexed);
+                    if !report.is_up_to_date() && args.repair {
+                        println!("repaired");
+                    }
+                },
+                None => match relayer.detect_reset(args.repair).await? {
+                    Some(seq) if args.repair => println!("reset at job {}, rebuilding", seq),
+                    Some(seq) => println!("reset at job {}", seq),
+                    None => println!("no reset detected"),
+                },
+            }
+        },
+        Command::Run | Command::Reindex(_) => {
+            config.validate()?;
+            let mut relayer = SQLRelayer::init(&config, reindex).await;
 
-    serve_res?;
-    relayer_res?;
+            let serve_job = tokio::spawn(async move { serve(config).await });
+            let relayer_job = tokio::spawn(async move { relayer.start().await });
+            let (serve_res, relayer_res) = tokio::join!(serve_job, relayer_job);
+
+            serve_res?;
+            relayer_res?;
+        },
+    }
 
     Ok(())
 }
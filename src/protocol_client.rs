@@ -1,73 +1,653 @@
-use reqwest::Client;
-use serde_json::json;
-use std::error::Error;
+use crate::backoff::Backoff;
+use crate::config::RpcConfig;
+use crate::types::{ComputeResult, JsonRpcError, ResultsPage, RpcResponse, TxKind, UpdatesQuery};
+use openrank_common::tx::Tx;
+use reqwest::{Client, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use thiserror::Error;
 
+/// Delay before the first retry of a failed call; later retries back off from there.
+const RETRY_INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const RETRY_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Error)]
+pub enum RpcError {
+    #[error("http request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("sequencer responded with http status {0}")]
+    Status(StatusCode),
+    #[error("failed to decode response: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[error("sequencer returned an error: {0}")]
+    Response(JsonRpcError),
+    #[error("batch response has no entry for request {0}")]
+    MissingResponse(u64),
+    #[error("{first} and {second} returned different compute results for job {seq}")]
+    Mismatch { seq: u64, first: String, second: String },
+}
+
+/// Result of a call: `None` when the sequencer answered with a null result.
+pub type RpcResult<T> = Result<Option<T>, RpcError>;
+
+impl RpcError {
+    /// Whether the call may succeed if sent again: timeouts, dropped connections,
+    /// server errors and rate limiting.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RpcError::Http(e) => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+            RpcError::Status(status) => {
+                status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS
+            },
+            RpcError::Decode(_)
+            | RpcError::Response(_)
+            | RpcError::MissingResponse(_)
+            | RpcError::Mismatch { .. } => false,
+        }
+    }
+}
+
+/// Counters of RPC call outcomes since the client was created.
+#[derive(Debug, Default)]
+pub struct RpcMetrics {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    not_found: AtomicU64,
+    transport_errors: AtomicU64,
+    decode_errors: AtomicU64,
+    response_errors: AtomicU64,
+    mismatches: AtomicU64,
+}
+
+/// Point-in-time copy of [`RpcMetrics`].
+#[derive(Debug, Clone, Copy)]
+pub struct RpcMetricsSnapshot {
+    pub requests: u64,
+    pub retries: u64,
+    pub not_found: u64,
+    pub transport_errors: u64,
+    pub decode_errors: u64,
+    pub response_errors: u64,
+    pub mismatches: u64,
+}
+
+impl RpcMetrics {
+    pub fn snapshot(&self) -> RpcMetricsSnapshot {
+        RpcMetricsSnapshot {
+            requests: self.requests.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            not_found: self.not_found.load(Ordering::Relaxed),
+            transport_errors: self.transport_errors.load(Ordering::Relaxed),
+            decode_errors: self.decode_errors.load(Ordering::Relaxed),
+            response_errors: self.response_errors.load(Ordering::Relaxed),
+            mismatches: self.mismatches.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record(&self, error: &RpcError) {
+        let counter = match error {
+            RpcError::Http(_) | RpcError::Status(_) => &self.transport_errors,
+            RpcError::Decode(_) => &self.decode_errors,
+            RpcError::Response(e) if e.is_not_found() => &self.not_found,
+            RpcError::Response(_) | RpcError::MissingResponse(_) => &self.response_errors,
+            RpcError::Mismatch { .. } => &self.mismatches,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Display for RpcMetricsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} requests, {} retries, {} not found, {} transport errors, {} decode errors, \
+             {} error responses, {} mismatches",
+            self.requests,
+            self.retries,
+            self.not_found,
+            self.transport_errors,
+            self.decode_errors,
+            self.response_errors,
+            self.mismatches
+        )
+    }
+}
+
+/// A sequencer endpoint and the health observed on it.
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    /// Moving average of response times, in microseconds; 0 until the first response.
+    latency_micros: AtomicU64,
+    errors: AtomicU64,
+    consecutive_errors: AtomicU64,
+}
+
+/// Point-in-time health of one endpoint.
+#[derive(Debug, Clone)]
+pub struct EndpointHealth {
+    pub url: String,
+    pub latency: Option<Duration>,
+    pub errors: u64,
+    pub consecutive_errors: u64,
+}
+
+impl Endpoint {
+    fn new(url: &str) -> Self {
+        Endpoint {
+            url: url.to_string(),
+            latency_micros: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            consecutive_errors: AtomicU64::new(0),
+        }
+    }
+
+    /// Records an answer, whatever it said. The latency average weighs the new sample 1/8.
+    fn record_response(&self, elapsed: Duration) {
+        let sample = elapsed.as_micros().max(1) as u64;
+        let average = self.latency_micros.load(Ordering::Relaxed);
+        let average = if average == 0 { sample } else { (average * 7 + sample) / 8 };
+        self.latency_micros.store(average, Ordering::Relaxed);
+        self.consecutive_errors.store(0, Ordering::Relaxed);
+    }
+
+    fn record_failure(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Lower is better: endpoints that are currently failing rank after every other one,
+    /// then faster endpoints rank first, and endpoints that have not answered yet last.
+    fn score(&self) -> (u64, u64) {
+        let latency = match self.latency_micros.load(Ordering::Relaxed) {
+            0 => u64::MAX,
+            latency => latency,
+        };
+        (self.consecutive_errors.load(Ordering::Relaxed), latency)
+    }
+
+    fn health(&self) -> EndpointHealth {
+        let latency = self.latency_micros.load(Ordering::Relaxed);
+        EndpointHealth {
+            url: self.url.clone(),
+            latency: (latency > 0).then(|| Duration::from_micros(latency)),
+            errors: self.errors.load(Ordering::Relaxed),
+            consecutive_errors: self.consecutive_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl fmt::Display for EndpointHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.latency {
+            Some(latency) => write!(f, "{}: {:?} average latency", self.url, latency)?,
+            None => write!(f, "{}: no responses yet", self.url)?,
+        }
+        write!(
+            f,
+            ", {} errors ({} consecutive)",
+            self.errors, self.consecutive_errors
+        )
+    }
+}
+
+/// JSON-RPC client for one or more sequencer endpoints. Each call goes to the healthiest
+/// endpoint first and fails over to the others when it fails.
 #[derive(Debug)]
 pub struct RpcClient {
     client: Client,
-    url: String,
+    endpoints: Vec<Endpoint>,
     request_id: AtomicU64,
+    max_retries: u32,
+    max_batch_size: usize,
+    cross_check: bool,
+    metrics: RpcMetrics,
 }
 
 impl RpcClient {
-    pub fn new(url: &str) -> Self {
-        RpcClient { client: Client::new(), url: url.to_string(), request_id: AtomicU64::new(1) }
+    pub fn new(config: &RpcConfig) -> Result<Self, RpcError> {
+        let client = Client::builder()
+            .connect_timeout(config.connect_timeout())
+            .timeout(config.request_timeout())
+            .build()?;
+
+        Ok(RpcClient {
+            client,
+            endpoints: config.endpoints.iter().map(|url| Endpoint::new(url)).collect(),
+            request_id: AtomicU64::new(1),
+            max_retries: config.max_retries,
+            max_batch_size: config.max_batch_size,
+            cross_check: config.cross_check,
+            metrics: RpcMetrics::default(),
+        })
+    }
+
+    pub fn metrics(&self) -> RpcMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Most calls the sequencer should be sent in one batch.
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    pub fn endpoint_health(&self) -> Vec<EndpointHealth> {
+        self.endpoints.iter().map(Endpoint::health).collect()
+    }
+
+    /// Indices of the endpoints, healthiest first.
+    fn ranked(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.endpoints.len()).collect();
+        order.sort_by_key(|&index| self.endpoints[index].score());
+        order
     }
 
     fn get_next_id(&self) -> u64 {
         self.request_id.fetch_add(1, Ordering::Relaxed)
     }
 
-    pub async fn sequencer_get_compute_result(
-        &self, id: u64,
-    ) -> Result<serde_json::Value, Box<dyn Error>> {
+    fn request(&self, method: &str, params: &Value) -> (u64, Value) {
         let request_id = self.get_next_id();
         let payload = json!({
             "jsonrpc": "2.0",
-            "method": "sequencer_get_compute_result",
-            "params": [id],
+            "method": method,
+            "params": params,
             "id": request_id,
         });
+        (request_id, payload)
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> RpcResult<T> {
+        self.call_from(&self.ranked(), method, &params).await.0
+    }
+
+    /// Sends a single call to the endpoints in `order` (see [`RpcClient::post_from`]).
+    /// Returns the result together with the index of the endpoint it came from.
+    async fn call_from<T: DeserializeOwned>(
+        &self, order: &[usize], method: &str, params: &Value,
+    ) -> (RpcResult<T>, usize) {
+        let (_, payload) = self.request(method, params);
+        let (response, index) = self.post_from(order, method, &payload).await;
+        let result = response.and_then(|response| {
+            let result =
+                serde_json::from_value(response).map_err(RpcError::from).and_then(into_result);
+            if let Err(e) = &result {
+                self.metrics.record(e);
+            }
+            result
+        });
+        (result, index)
+    }
+
+    /// Sends one JSON-RPC batch with a `method` call per entry of `params`. Responses are
+    /// matched to their calls by id and returned in the order of `params`, each with its
+    /// own result; the outer error is for a batch that failed as a whole.
+    async fn call_batch<T: DeserializeOwned>(
+        &self, method: &str, params: &[Value],
+    ) -> Result<Vec<RpcResult<T>>, RpcError> {
+        let (ids, payload): (Vec<u64>, Vec<Value>) =
+            params.iter().map(|params| self.request(method, params)).unzip();
+
+        let (response, _) = self.post_from(&self.ranked(), method, &Value::Array(payload)).await;
+        let mut responses = decode_batch(response?).map_err(|e| {
+            self.metrics.record(&e);
+            e
+        })?;
+
+        Ok(ids
+            .into_iter()
+            .map(|id| {
+                let result = take_result(&mut responses, id);
+                if let Err(e) = &result {
+                    self.metrics.record(e);
+                }
+                result
+            })
+            .collect())
+    }
+
+    /// Posts `payload` to the endpoints in `order`, moving on to the next one when an
+    /// endpoint fails to answer with JSON. Once every endpoint has failed, the round is
+    /// retried with jittered exponential backoff, up to `max_retries` times, if any of the
+    /// failures was transient. Returns the response together with the index of the
+    /// endpoint it came from (or failed last on).
+    async fn post_from(
+        &self, order: &[usize], method: &str, payload: &Value,
+    ) -> (Result<Value, RpcError>, usize) {
+        let mut backoff = Backoff::new(RETRY_INITIAL_BACKOFF, RETRY_MAX_BACKOFF).with_jitter();
+        let mut retries = 0;
+        loop {
+            let mut last_error = None;
+            let mut transient = false;
+            for &index in order {
+                let endpoint = &self.endpoints[index];
+                self.metrics.requests.fetch_add(1, Ordering::Relaxed);
+                let started = Instant::now();
+
+                match self.post(endpoint, payload).await {
+                    Ok(response) => {
+                        endpoint.record_response(started.elapsed());
+                        return (Ok(response), index);
+                    },
+                    Err(e) => {
+                        self.metrics.record(&e);
+                        endpoint.record_failure();
+                        log::warn!("{} failed on {}: {}", method, endpoint.url, e);
+                        transient |= e.is_transient();
+                        last_error = Some((e, index));
+                    },
+                }
+            }
 
-        let response = self.client.post(&self.url).json(&payload).send().await?.json().await?;
+            let (e, index) = last_error.expect("at least one endpoint is configured");
+            if !transient || retries >= self.max_retries {
+                return (Err(e), index);
+            }
+            let delay = backoff.next_delay();
+            log::warn!(
+                "{} failed on every endpoint, retrying in {:?}",
+                method,
+                delay
+            );
+            tokio::time::sleep(delay).await;
+            retries += 1;
+            self.metrics.retries.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    async fn post(&self, endpoint: &Endpoint, payload: &Value) -> Result<Value, RpcError> {
+        let response = self.client.post(&endpoint.url).json(payload).send().await?;
+        if !response.status().is_success() {
+            return Err(RpcError::Status(response.status()));
+        }
+        Ok(response.json().await?)
+    }
+
+    /// Fetches the compute result of job `id`. A "not found" error from the sequencer means
+    /// the job has not been computed yet and is returned as `Ok(None)`; any other error
+    /// response is returned as an error.
+    ///
+    /// With cross-checking enabled, the result is also fetched from a second endpoint. It
+    /// is only returned once both endpoints have it, and an error is returned if they
+    /// disagree.
+    pub async fn sequencer_get_compute_result(&self, id: u64) -> RpcResult<ComputeResult> {
+        let order = self.ranked();
+        let (result, answered) = self.get_compute_result_from(&order, id).await;
+        if !self.cross_check || self.endpoints.len() < 2 {
+            return result;
+        }
+        let result = result?;
 
-        Ok(response)
+        let others: Vec<usize> = order.into_iter().filter(|&index| index != answered).collect();
+        let (other, checked) = self.get_compute_result_from(&others, id).await;
+        match (result, other?) {
+            (Some(first), Some(second)) if first != second => {
+                self.metrics.mismatches.fetch_add(1, Ordering::Relaxed);
+                Err(RpcError::Mismatch {
+                    seq: id,
+                    first: self.endpoints[answered].url.clone(),
+                    second: self.endpoints[checked].url.clone(),
+                })
+            },
+            (Some(result), Some(_)) => Ok(Some(result)),
+            // One of the endpoints has not caught up with the job yet.
+            _ => Ok(None),
+        }
     }
 
-    pub async fn _sequencer_get_results(
+    async fn get_compute_result_from(
+        &self, order: &[usize], id: u64,
+    ) -> (RpcResult<ComputeResult>, usize) {
+        let (result, index) =
+            self.call_from(order, "sequencer_get_compute_result", &json!([id])).await;
+        match result {
+            Err(RpcError::Response(error)) if error.is_not_found() => (Ok(None), index),
+            result => (result, index),
+        }
+    }
+
+    /// Fetches up to `size` of the final scores of a job, starting at entry `start`.
+    pub async fn sequencer_get_results(
         &self, request_tx_hash: &str, start: u64, size: u64,
-    ) -> Result<serde_json::Value, Box<dyn Error>> {
-        let request_id = self.get_next_id();
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "method": "sequencer_get_results",
-            "params": [{
+    ) -> RpcResult<ResultsPage> {
+        self.call(
+            "sequencer_get_results",
+            json!([{
                 "request_tx_hash": request_tx_hash,
                 "start": start,
                 "size": size,
-            }],
-            "id": request_id,
-        });
+            }]),
+        )
+        .await
+    }
 
-        let response = self.client.post(&self.url).json(&payload).send().await?.json().await?;
+    /// Fetches up to `size` TrustUpdate transactions, starting at the `from`-th one the
+    /// sequencer received.
+    pub async fn sequencer_get_trust_updates(&self, from: u64, size: u64) -> RpcResult<Vec<Tx>> {
+        let (method, params) = trust_updates_call(from, size);
+        self.call(method, params).await
+    }
 
-        Ok(response)
+    /// Fetches up to `size` SeedUpdate transactions, starting at the `from`-th one the
+    /// sequencer received.
+    pub async fn sequencer_get_seed_updates(&self, from: u64, size: u64) -> RpcResult<Vec<Tx>> {
+        let (method, params) = seed_updates_call(from, size);
+        self.call(method, params).await
     }
 
-    pub async fn sequencer_get_tx(
-        &self, tx_type: &str, tx_hash: &str,
-    ) -> Result<serde_json::Value, Box<dyn Error>> {
-        let request_id = self.get_next_id();
-        let payload = json!({
-            "jsonrpc": "2.0",
-            "method": "sequencer_get_tx",
-            "params": [tx_type, tx_hash],
-            "id": request_id,
-        });
+    /// Fetches several transactions in one batch, with a result per transaction.
+    pub async fn sequencer_get_txs(
+        &self, txs: &[(TxKind, String)],
+    ) -> Result<Vec<RpcResult<Tx>>, RpcError> {
+        let params: Vec<Value> =
+            txs.iter().map(|(kind, tx_hash)| json!([kind.as_str(), tx_hash])).collect();
+        self.call_batch("sequencer_get_tx", &params).await
+    }
+}
+
+fn trust_updates_call(from: u64, size: u64) -> (&'static str, Value) {
+    (
+        "sequencer_get_trust_updates",
+        json!([UpdatesQuery { from, size }]),
+    )
+}
+
+fn seed_updates_call(from: u64, size: u64) -> (&'static str, Value) {
+    (
+        "sequencer_get_seed_updates",
+        json!([UpdatesQuery { from, size }]),
+    )
+}
+
+fn into_result<T: DeserializeOwned>(response: RpcResponse<Value>) -> RpcResult<T> {
+    match (response.error, response.result) {
+        (Some(error), _) => Err(RpcError::Response(error)),
+        (None, Some(result)) => Ok(Some(serde_json::from_value(result)?)),
+        (None, None) => Ok(None),
+    }
+}
+
+/// Result of request `id` out of a decoded batch.
+fn take_result<T: DeserializeOwned>(
+    responses: &mut HashMap<u64, RpcResponse<Value>>, id: u64,
+) -> RpcResult<T> {
+    match responses.remove(&id) {
+        Some(response) => into_result(response),
+        None => Err(RpcError::MissingResponse(id)),
+    }
+}
+
+/// Splits a batch response into its entries, keyed by request id.
+fn decode_batch(response: Value) -> Result<HashMap<u64, RpcResponse<Value>>, RpcError> {
+    match response {
+        Value::Array(entries) => {
+            let mut responses = HashMap::with_capacity(entries.len());
+            for entry in entries {
+                let response: RpcResponse<Value> = serde_json::from_value(entry)?;
+                // Entries without an id answer a request the server could not parse.
+                if let Some(id) = response.id {
+                    responses.insert(id, response);
+                }
+            }
+            Ok(responses)
+        },
+        // A server rejecting the batch as a whole answers with a single error object.
+        response => match serde_json::from_value::<RpcResponse<Value>>(response)?.error {
+            Some(error) => Err(RpcError::Response(error)),
+            None => Err(RpcError::Decode(serde::de::Error::custom(
+                "expected an array in response to a batch",
+            ))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_batch, into_result, seed_updates_call, take_result, trust_updates_call, RpcClient,
+        RpcError,
+    };
+    use crate::config::RpcConfig;
+    use crate::types::RpcResponse;
+    use serde_json::{json, Value};
+    use std::time::Duration;
+
+    fn client(endpoints: usize) -> RpcClient {
+        RpcClient::new(&RpcConfig {
+            endpoints: (0..endpoints).map(|i| format!("http://sequencer-{}", i)).collect(),
+            connect_timeout_secs: 5,
+            request_timeout_secs: 30,
+            max_retries: 0,
+            max_batch_size: 50,
+            cross_check: false,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn endpoints_rank_by_failures_then_latency() {
+        let client = client(4);
+        assert_eq!(client.ranked(), [0, 1, 2, 3]);
+
+        client.endpoints[1].record_response(Duration::from_millis(50));
+        client.endpoints[2].record_response(Duration::from_millis(10));
+        client.endpoints[3].record_response(Duration::from_millis(1));
+        client.endpoints[3].record_failure();
+        // Endpoint 0 has never answered, so it ranks after the measured healthy ones.
+        assert_eq!(client.ranked(), [2, 1, 0, 3]);
+
+        client.endpoints[0].record_failure();
+        client.endpoints[0].record_failure();
+        assert_eq!(client.ranked(), [2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn updates_requests_name_the_method_and_query() {
+        let client = client(1);
+        for ((method, params), expected) in [
+            (trust_updates_call(10, 5), "sequencer_get_trust_updates"),
+            (seed_updates_call(10, 5), "sequencer_get_seed_updates"),
+        ] {
+            let (id, payload) = client.request(method, &params);
+            assert_eq!(
+                payload,
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": expected,
+                    "params": [{"from": 10, "size": 5}],
+                    "id": id,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn batch_results_are_matched_by_id() {
+        let response = json!([
+            {"jsonrpc": "2.0", "id": 2, "result": "second"},
+            {"jsonrpc": "2.0", "id": 1, "result": "first"},
+        ]);
+        let mut responses = decode_batch(response).unwrap();
+
+        assert_eq!(
+            take_result::<String>(&mut responses, 1).unwrap(),
+            Some("first".into())
+        );
+        assert_eq!(
+            take_result::<String>(&mut responses, 2).unwrap(),
+            Some("second".into())
+        );
+    }
+
+    #[test]
+    fn batch_entries_without_id_are_skipped() {
+        let response = json!([
+            {"jsonrpc": "2.0", "id": null, "error": {"code": -32700, "message": "Parse error"}},
+            {"jsonrpc": "2.0", "id": 1, "result": 7},
+        ]);
+        let responses = decode_batch(response).unwrap();
+
+        assert_eq!(responses.len(), 1);
+        assert!(responses.contains_key(&1));
+    }
+
+    #[test]
+    fn missing_batch_entry_is_an_error() {
+        let response = json!([{"jsonrpc": "2.0", "id": 1, "result": 7}]);
+        let mut responses = decode_batch(response).unwrap();
+
+        let result = take_result::<u64>(&mut responses, 2);
+        assert!(matches!(result, Err(RpcError::MissingResponse(2))));
+    }
+
+    #[test]
+    fn batch_entry_errors_stay_per_entry() {
+        let response = json!([
+            {"jsonrpc": "2.0", "id": 1, "error": {"code": -32000, "message": "Tx not found"}},
+            {"jsonrpc": "2.0", "id": 2, "result": 7},
+        ]);
+        let mut responses = decode_batch(response).unwrap();
+
+        match take_result::<u64>(&mut responses, 1) {
+            Err(RpcError::Response(error)) => assert!(error.is_not_found()),
+            other => panic!("expected an error response, got {:?}", other),
+        }
+        assert_eq!(take_result::<u64>(&mut responses, 2).unwrap(), Some(7));
+    }
+
+    #[test]
+    fn batch_rejected_as_a_whole_is_an_error() {
+        let response =
+            json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32600, "message": "Invalid"}});
+        assert!(matches!(decode_batch(response), Err(RpcError::Response(_))));
+    }
+
+    #[test]
+    fn non_array_batch_without_error_is_a_decode_error() {
+        let response = json!({"jsonrpc": "2.0", "id": 1, "result": 7});
+        assert!(matches!(decode_batch(response), Err(RpcError::Decode(_))));
+    }
+
+    #[test]
+    fn into_result_decodes_result_null_and_error() {
+        let response = |value: Value| serde_json::from_value::<RpcResponse<Value>>(value).unwrap();
+
+        let ok = into_result::<u64>(response(json!({"id": 1, "result": 7})));
+        assert_eq!(ok.unwrap(), Some(7));
+
+        let null = into_result::<u64>(response(json!({"id": 1, "result": null})));
+        assert_eq!(null.unwrap(), None);
 
-        let response = self.client.post(&self.url).json(&payload).send().await?.json().await?;
+        let error = into_result::<u64>(response(
+            json!({"id": 1, "error": {"code": -32603, "message": "Internal error"}}),
+        ));
+        assert!(matches!(error, Err(RpcError::Response(_))));
 
-        Ok(response)
+        let mistyped = into_result::<u64>(response(json!({"id": 1, "result": "seven"})));
+        assert!(matches!(mistyped, Err(RpcError::Decode(_))));
     }
 }
@@ -0,0 +1,77 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// How much of the database `reindex` rebuilds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReindexMode {
+    /// Drop every table and resync from job 0.
+    Full,
+    /// Delete jobs from this seq number onwards and rewind the cursor to it.
+    From(u64),
+    /// Delete and re-fetch the jobs in `start..end`, leaving the cursor where it is.
+    Range(u64, u64),
+}
+
+/// A range of job seq numbers, `start..end` with the end excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeqRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl FromStr for SeqRange {
+    type Err = String;
+
+    /// Parses a `<a>..<b>` range, end exclusive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = s.split_once("..").ok_or_else(|| format!("invalid range `{}`", s))?;
+        let start: u64 = start.parse().map_err(|e| format!("invalid range `{}`: {}", s, e))?;
+        let end: u64 = end.parse().map_err(|e| format!("invalid range `{}`: {}", s, e))?;
+        if start >= end {
+            return Err(format!("invalid range `{}`: start must be below end", s));
+        }
+        Ok(SeqRange { start, end })
+    }
+}
+
+impl fmt::Display for SeqRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+impl fmt::Display for ReindexMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReindexMode::Full => write!(f, "full"),
+            ReindexMode::From(seq) => write!(f, "from {}", seq),
+            ReindexMode::Range(start, end) => write!(f, "range {}..{}", start, end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeqRange;
+
+    #[test]
+    fn parses_range() {
+        assert_eq!(
+            "3..10".parse::<SeqRange>(),
+            Ok(SeqRange { start: 3, end: 10 })
+        );
+    }
+
+    #[test]
+    fn rejects_reversed_and_empty_ranges() {
+        assert!("10..3".parse::<SeqRange>().is_err());
+        assert!("5..5".parse::<SeqRange>().is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_ranges() {
+        for input in ["", "full", "3", "3..", "..10", "a..b", "3...10", "-1..4"] {
+            assert!(input.parse::<SeqRange>().is_err(), "accepted `{}`", input);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use super::{from_bigint, to_bigint, DbTx, Page, Paged, Storage};
+use crate::types::JobStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::QueryBuilder;
+
+/// A row of the `jobs` table.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Job {
+    pub id: i64,
+    pub transaction_hashes: Vec<String>,
+    pub seq_number: i64,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub status: JobStatus,
+}
+
+impl Storage {
+    /// Jobs, paginated by seq number.
+    pub async fn list_jobs(&self, page: &Page) -> Result<Paged<Job>, sqlx::Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, transaction_hashes, seq_number, timestamp, status FROM jobs WHERE 1=1",
+        );
+        page.push_clause(&mut query, "seq_number");
+
+        let jobs = query.build_query_as::<Job>().fetch_all(&self.pool).await?;
+        Ok(page.finish(jobs))
+    }
+
+    /// Jobs with any of the given seq numbers.
+    pub async fn jobs_by_seq_numbers(&self, seq_numbers: &[i64]) -> Result<Vec<Job>, sqlx::Error> {
+        sqlx::query_as::<_, Job>(
+            "SELECT id, transaction_hashes, seq_number, timestamp, status FROM jobs WHERE seq_number = ANY($1)",
+        )
+        .bind(seq_numbers)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn count_jobs(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM jobs").fetch_one(&self.pool).await
+    }
+
+    /// Jobs from `from` on without a final status, with their stored transaction hashes.
+    pub async fn pending_jobs(&self, from: u64) -> Result<Vec<(u64, Vec<String>)>, sqlx::Error> {
+        let rows: Vec<(i64, Vec<String>)> = sqlx::query_as(
+            "SELECT seq_number, transaction_hashes FROM jobs
+             WHERE seq_number >= $1 AND status NOT IN ('verified', 'rejected')
+             ORDER BY seq_number",
+        )
+        .bind(to_bigint(from)?)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(|(seq, hashes)| Ok((from_bigint(seq)?, hashes))).collect()
+    }
+
+    /// Up to `limit` jobs stored without a timestamp, lowest seq number first.
+    pub async fn jobs_without_timestamp(&self, limit: u64) -> Result<Vec<u64>, sqlx::Error> {
+        let seq_numbers: Vec<i64> = sqlx::query_scalar(
+            "SELECT seq_number FROM jobs WHERE timestamp IS NULL ORDER BY seq_number LIMIT $1",
+        )
+        .bind(to_bigint(limit)?)
+        .fetch_all(&self.pool)
+        .await?;
+        seq_numbers.into_iter().map(from_bigint).collect()
+    }
+
+    pub async fn set_job_timestamp(
+        &self, seq_number: u64, timestamp: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE jobs SET timestamp = $2 WHERE seq_number = $1")
+            .bind(to_bigint(seq_number)?)
+            .bind(timestamp)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Stored transaction hashes of a job, or `None` if the job is not indexed.
+    pub async fn job_transaction_hashes(
+        &self, seq_number: u64,
+    ) -> Result<Option<Vec<String>>, sqlx::Error> {
+        sqlx::query_scalar("SELECT transaction_hashes FROM jobs WHERE seq_number = $1")
+            .bind(to_bigint(seq_number)?)
+            .fetch_optional(&self.pool)
+            .await
+    }
+}
+
+pub(super) async fn insert(
+    db_tx: &mut DbTx<'_>, seq_number: i64, timestamp: DateTime<Utc>, transaction_hashes: &[String],
+    status: JobStatus,
+) -> Result<(), sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO jobs (transaction_hashes, seq_number, timestamp, status) VALUES ($1, $2, $3, $4) ON CONFLICT (seq_number) DO NOTHING",
+    )
+    .bind(transaction_hashes)
+    .bind(seq_number)
+    .bind(timestamp)
+    .bind(status)
+    .execute(&mut **db_tx)
+    .await;
+
+    match result {
+        Ok(result) => {
+            if result.rows_affected() == 0 {
+                log::warn!(
+                    "No rows inserted, possibly due to conflict with seq_number '{}'",
+                    seq_number
+                );
+            } else {
+                log::info!(
+                    "Inserted {} row(s) into jobs table.",
+                    result.rows_affected()
+                );
+            }
+            Ok(())
+        },
+        Err(e) => {
+            log::error!("Error inserting job: {}", e);
+            Err(e)
+        },
+    }
+}
+
+pub(super) async fn delete_range(
+    db_tx: &mut DbTx<'_>, from: i64, to: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM jobs WHERE seq_number >= $1 AND ($2::BIGINT IS NULL OR seq_number < $2)",
+    )
+    .bind(from)
+    .bind(to)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(result.rows_affected())
+}
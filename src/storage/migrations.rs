@@ -0,0 +1,88 @@
+use sqlx::PgPool;
+
+/// A numbered schema change, embedded in the binary at build time.
+pub struct Migration {
+    pub version: i32,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+/// All schema migrations, in the order they are applied.
+/// Append new migrations to the end; never edit one that has already been released.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial",
+        sql: include_str!("../../assets/migrations/0001_initial.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "scores",
+        sql: include_str!("../../assets/migrations/0002_scores.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "score_entries",
+        sql: include_str!("../../assets/migrations/0003_score_entries.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "trust_graph",
+        sql: include_str!("../../assets/migrations/0004_trust_graph.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "job_status",
+        sql: include_str!("../../assets/migrations/0005_job_status.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "bigint",
+        sql: include_str!("../../assets/migrations/0006_bigint.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "transaction_filters",
+        sql: include_str!("../../assets/migrations/0007_transaction_filters.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "score_ranking",
+        sql: include_str!("../../assets/migrations/0008_score_ranking.sql"),
+    },
+];
+
+/// Applies every migration not yet recorded in `schema_migrations`, each in its own
+/// database transaction.
+pub async fn run(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name VARCHAR NOT NULL,
+            applied_at TIMESTAMP NOT NULL DEFAULT NOW()
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    let applied: Vec<i32> =
+        sqlx::query_scalar("SELECT version FROM schema_migrations").fetch_all(pool).await?;
+
+    for migration in MIGRATIONS {
+        if applied.contains(&migration.version) {
+            continue;
+        }
+
+        log::info!("Applying migration {:04}_{}", migration.version, migration.name);
+        let mut db_tx = pool.begin().await?;
+        sqlx::raw_sql(migration.sql).execute(&mut *db_tx).await?;
+        sqlx::query("INSERT INTO schema_migrations (version, name) VALUES ($1, $2)")
+            .bind(migration.version)
+            .bind(migration.name)
+            .execute(&mut *db_tx)
+            .await?;
+        db_tx.commit().await?;
+    }
+
+    Ok(())
+}
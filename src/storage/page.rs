@@ -0,0 +1,61 @@
+use sqlx::{Postgres, QueryBuilder};
+
+/// Order rows are listed in, by id or seq number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// Oldest first.
+    #[default]
+    Forward,
+    /// Newest first.
+    Backward,
+}
+
+/// A window of a listing paginated by key: the rows strictly between `after` and `before`
+/// in `direction`, `limit` of them taken from the start, or from the end if `from_end`.
+#[derive(Debug, Clone, Copy)]
+pub struct Page {
+    pub after: Option<i64>,
+    pub before: Option<i64>,
+    pub limit: i64,
+    pub direction: Direction,
+    pub from_end: bool,
+}
+
+/// Rows of a page in listing order, and whether more rows lie past the limit.
+#[derive(Debug, Clone)]
+pub struct Paged<T> {
+    pub rows: Vec<T>,
+    pub has_more: bool,
+}
+
+impl Page {
+    /// Appends the key bounds, ordering and limit of the page on `key` to a query whose
+    /// `WHERE` clause is open. One extra row is read to tell whether there are more.
+    pub(super) fn push_clause(&self, query: &mut QueryBuilder<'_, Postgres>, key: &str) {
+        let (after_op, before_op) = match self.direction {
+            Direction::Forward => (">", "<"),
+            Direction::Backward => ("<", ">"),
+        };
+        if let Some(after) = self.after {
+            query.push(format_args!(" AND {} {} ", key, after_op)).push_bind(after);
+        }
+        if let Some(before) = self.before {
+            query.push(format_args!(" AND {} {} ", key, before_op)).push_bind(before);
+        }
+
+        // A page taken from the end is read in reverse and flipped back in `finish`.
+        let ascending = (self.direction == Direction::Forward) != self.from_end;
+        let order = if ascending { "ASC" } else { "DESC" };
+        query.push(format_args!(" ORDER BY {} {}", key, order));
+        query.push(" LIMIT ").push_bind(self.limit + 1);
+    }
+
+    pub(super) fn finish<T>(&self, mut rows: Vec<T>) -> Paged<T> {
+        let has_more = rows.len() as i64 > self.limit;
+        rows.truncate(self.limit.max(0) as usize);
+        if self.from_end {
+            rows.reverse();
+        }
+        Paged { rows, has_more }
+    }
+}
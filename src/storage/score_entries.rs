@@ -0,0 +1,45 @@
+use super::DbTx;
+use crate::types::IndexedScoreEntry;
+
+/// Inserts the score entries of a job's ComputeScores transactions.
+pub(super) async fn insert(
+    db_tx: &mut DbTx<'_>, job_seq_number: i64, entries: &[IndexedScoreEntry],
+) -> Result<(), sqlx::Error> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let tx_hashes: Vec<&str> = entries.iter().map(|entry| entry.tx_hash.as_str()).collect();
+    let peer_ids: Vec<&str> = entries.iter().map(|entry| entry.peer_id.as_str()).collect();
+    let values: Vec<f64> = entries.iter().map(|entry| entry.value).collect();
+    let result = sqlx::query(
+        "INSERT INTO score_entries (job_seq_number, tx_hash, peer_id, value)
+         SELECT $1, * FROM UNNEST($2::VARCHAR[], $3::VARCHAR[], $4::DOUBLE PRECISION[])
+         ON CONFLICT (tx_hash, peer_id) DO NOTHING",
+    )
+    .bind(job_seq_number)
+    .bind(&tx_hashes)
+    .bind(&peer_ids)
+    .bind(&values)
+    .execute(&mut **db_tx)
+    .await?;
+    log::info!(
+        "Inserted {} row(s) into score_entries table.",
+        result.rows_affected()
+    );
+    Ok(())
+}
+
+pub(super) async fn delete_range(
+    db_tx: &mut DbTx<'_>, from: i64, to: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM score_entries
+         WHERE job_seq_number >= $1 AND ($2::BIGINT IS NULL OR job_seq_number < $2)",
+    )
+    .bind(from)
+    .bind(to)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(result.rows_affected())
+}
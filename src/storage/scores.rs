@@ -0,0 +1,167 @@
+use super::{from_bigint, state, to_bigint, DbTx, Paged, Storage};
+use crate::types::PeerScore;
+use serde::{Deserialize, Serialize};
+use sqlx::QueryBuilder;
+
+/// Cursor of the first job whose scores have not all been stored yet.
+pub(super) const CURSOR_KEY: &str = "scores";
+
+/// A stored score with its rank in the job, 1 for the highest. Ties keep the order the
+/// sequencer returned them in.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RankedScore {
+    pub job_seq_number: i64,
+    pub peer_id: String,
+    pub value: f64,
+    pub position: i64,
+    pub rank: i64,
+}
+
+impl RankedScore {
+    pub fn key(&self) -> ScoreKey {
+        ScoreKey { value: self.value, position: self.position, rank: self.rank }
+    }
+}
+
+/// Place of a score in the ranking of its job, to page on.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreKey {
+    pub value: f64,
+    pub position: i64,
+    pub rank: i64,
+}
+
+impl Storage {
+    /// Latest job whose scores are all stored, among the jobs computed for `domain` if
+    /// given.
+    pub async fn latest_scored_job(
+        &self, domain: Option<&str>,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT MAX(job_seq_number) FROM scores
+             WHERE job_seq_number < (SELECT COALESCE(MAX(last_processed_key), 0) FROM state WHERE key_name = $1)
+               AND ($2::VARCHAR IS NULL OR job_seq_number IN (
+                   SELECT job_seq_number FROM transactions
+                   WHERE type = 'compute_request' AND body -> 'ComputeRequest' ->> 'domain_id' = $2
+               ))",
+        )
+        .bind(CURSOR_KEY)
+        .bind(domain)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Up to `limit` scores of a job ranked right after `after`, or from the top.
+    pub async fn top_scores(
+        &self, job_seq_number: i64, after: Option<ScoreKey>, limit: i64,
+    ) -> Result<Paged<RankedScore>, sqlx::Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT job_seq_number, peer_id, value, position, 0::BIGINT AS rank FROM scores
+             WHERE job_seq_number = ",
+        );
+        query.push_bind(job_seq_number);
+        if let Some(after) = after {
+            query.push(" AND (-value, position) > (").push_bind(-after.value);
+            query.push(", ").push_bind(after.position).push(")");
+        }
+        query.push(" ORDER BY -value, position LIMIT ").push_bind(limit + 1);
+
+        let mut scores = query.build_query_as::<RankedScore>().fetch_all(&self.pool).await?;
+        let has_more = scores.len() as i64 > limit;
+        scores.truncate(limit.max(0) as usize);
+
+        let first_rank = after.map_or(1, |after| after.rank + 1);
+        for (rank, score) in (first_rank..).zip(&mut scores) {
+            score.rank = rank;
+        }
+        Ok(Paged { rows: scores, has_more })
+    }
+
+    /// Score and rank of a peer in a job, the best one if it is listed more than once.
+    pub async fn peer_score(
+        &self, job_seq_number: i64, peer_id: &str,
+    ) -> Result<Option<RankedScore>, sqlx::Error> {
+        sqlx::query_as::<_, RankedScore>(
+            "SELECT s.job_seq_number, s.peer_id, s.value, s.position,
+                    1 + (SELECT COUNT(*) FROM scores AS higher
+                         WHERE higher.job_seq_number = s.job_seq_number
+                           AND (-higher.value, higher.position) < (-s.value, s.position)) AS rank
+             FROM scores AS s
+             WHERE s.job_seq_number = $1 AND s.peer_id = $2
+             ORDER BY -s.value, s.position
+             LIMIT 1",
+        )
+        .bind(job_seq_number)
+        .bind(peer_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn count_scores(&self, job_seq_number: i64) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM scores WHERE job_seq_number = $1")
+            .bind(job_seq_number)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Position to resume paging the scores of a job from: one past the last stored entry.
+    pub async fn next_score_position(&self, job_seq_number: u64) -> Result<u64, sqlx::Error> {
+        let position: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(position) + 1, 0) FROM scores WHERE job_seq_number = $1",
+        )
+        .bind(to_bigint(job_seq_number)?)
+        .fetch_one(&self.pool)
+        .await?;
+        from_bigint(position)
+    }
+
+    /// Writes a page of scores starting at `start`. When `complete` is set, the page is the
+    /// last one of the job and the scores cursor moves past it in the same transaction.
+    pub async fn commit_scores_page(
+        &self, job_seq_number: u64, start: u64, entries: &[PeerScore], complete: bool,
+    ) -> Result<(), sqlx::Error> {
+        let job_seq_number = to_bigint(job_seq_number)?;
+        let start = to_bigint(start)?;
+        let mut db_tx = self.pool.begin().await?;
+
+        let positions: Vec<i64> = (start..).take(entries.len()).collect();
+        let peer_ids: Vec<&str> = entries.iter().map(|entry| entry.id.as_str()).collect();
+        let values: Vec<f64> = entries.iter().map(|entry| entry.value).collect();
+        let result = sqlx::query(
+            "INSERT INTO scores (job_seq_number, position, peer_id, value)
+             SELECT $1, * FROM UNNEST($2::BIGINT[], $3::VARCHAR[], $4::DOUBLE PRECISION[])
+             ON CONFLICT (job_seq_number, position) DO NOTHING",
+        )
+        .bind(job_seq_number)
+        .bind(&positions)
+        .bind(&peer_ids)
+        .bind(&values)
+        .execute(&mut *db_tx)
+        .await?;
+        log::info!(
+            "Inserted {} score(s) for job {}.",
+            result.rows_affected(),
+            job_seq_number
+        );
+
+        if complete {
+            state::save_last_processed_key(&mut db_tx, CURSOR_KEY, job_seq_number + 1).await?;
+        }
+
+        db_tx.commit().await
+    }
+}
+
+pub(super) async fn delete_range(
+    db_tx: &mut DbTx<'_>, from: i64, to: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM scores
+         WHERE job_seq_number >= $1 AND ($2::BIGINT IS NULL OR job_seq_number < $2)",
+    )
+    .bind(from)
+    .bind(to)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,48 @@
+use super::{from_bigint, DbTx, Storage};
+
+impl Storage {
+    pub async fn load_last_processed_key(
+        &self, key_name: &str,
+    ) -> Result<Option<u64>, sqlx::Error> {
+        let key: Option<i64> =
+            sqlx::query_scalar("SELECT last_processed_key FROM state WHERE key_name = $1")
+                .bind(key_name)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        key.map(from_bigint).transpose()
+    }
+}
+
+/// Advances the `key_name` cursor to `key`. The cursor never moves backwards here, so
+/// re-indexing an old job does not rewind it; use `rewind_last_processed_key` for that.
+pub(super) async fn save_last_processed_key(
+    db_tx: &mut DbTx<'_>, key_name: &str, key: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO state (key_name, last_processed_key, updated_at)
+         VALUES ($1, $2, NOW())
+         ON CONFLICT (key_name)
+         DO UPDATE SET last_processed_key = GREATEST(state.last_processed_key, $2), updated_at = NOW()",
+    )
+    .bind(key_name)
+    .bind(key)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
+
+/// Moves the `key_name` cursor back to `key` if it is past it.
+pub(super) async fn rewind_last_processed_key(
+    db_tx: &mut DbTx<'_>, key_name: &str, key: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE state SET last_processed_key = LEAST(last_processed_key, $2), updated_at = NOW()
+         WHERE key_name = $1",
+    )
+    .bind(key_name)
+    .bind(key)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use super::{to_bigint, DbTx, Page, Paged, Storage};
+use crate::types::TxKind;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Postgres, QueryBuilder};
+
+/// A row of the `transactions` table.
+#[derive(Debug, Clone, sqlx::FromRow, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: i64,
+    pub body: Value,
+    #[sqlx(rename = "type")]
+    pub type_: String,
+    pub hash: String,
+    pub job_seq_number: i64,
+    pub to: String,
+    pub from: String,
+}
+
+/// Optional filters for `Storage::list_transactions`. Ranges include their start and
+/// exclude their end.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionFilter {
+    pub hash: Option<String>,
+    pub kind: Option<TxKind>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub job_seq_number: Option<i64>,
+    pub job_seq_from: Option<i64>,
+    pub job_seq_to: Option<i64>,
+    /// Bounds on the timestamp of the job a transaction belongs to.
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TransactionFilter {
+    /// Appends the filter to a query whose `WHERE` clause is open.
+    fn push_conditions<'a>(&'a self, query: &mut QueryBuilder<'a, Postgres>) {
+        if let Some(hash) = &self.hash {
+            query.push(" AND hash = ").push_bind(hash);
+        }
+        if let Some(kind) = self.kind {
+            query.push(" AND type = ").push_bind(kind.as_str());
+        }
+        if let Some(from) = &self.from {
+            query.push(" AND \"from\" = ").push_bind(from);
+        }
+        if let Some(to) = &self.to {
+            query.push(" AND \"to\" = ").push_bind(to);
+        }
+        if let Some(seq) = self.job_seq_number {
+            query.push(" AND job_seq_number = ").push_bind(seq);
+        }
+        if let Some(seq) = self.job_seq_from {
+            query.push(" AND job_seq_number >= ").push_bind(seq);
+        }
+        if let Some(seq) = self.job_seq_to {
+            query.push(" AND job_seq_number < ").push_bind(seq);
+        }
+        if self.since.is_some() || self.until.is_some() {
+            query.push(" AND job_seq_number IN (SELECT seq_number FROM jobs WHERE 1=1");
+            if let Some(since) = self.since {
+                query.push(" AND timestamp >= ").push_bind(since);
+            }
+            if let Some(until) = self.until {
+                query.push(" AND timestamp < ").push_bind(until);
+            }
+            query.push(")");
+        }
+    }
+}
+
+impl Storage {
+    /// Transactions matching `filter`, paginated by id.
+    pub async fn list_transactions(
+        &self, filter: &TransactionFilter, page: &Page,
+    ) -> Result<Paged<Transaction>, sqlx::Error> {
+        let mut query = QueryBuilder::new(
+            "SELECT id, body, type, hash, job_seq_number, \"to\", \"from\" FROM transactions WHERE 1=1",
+        );
+        filter.push_conditions(&mut query);
+        page.push_clause(&mut query, "id");
+
+        let transactions = query.build_query_as::<Transaction>().fetch_all(&self.pool).await?;
+        Ok(page.finish(transactions))
+    }
+
+    /// Number of transactions matching `filter`.
+    pub async fn count_transactions(&self, filter: &TransactionFilter) -> Result<i64, sqlx::Error> {
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM transactions WHERE 1=1");
+        filter.push_conditions(&mut query);
+        query.build_query_scalar().fetch_one(&self.pool).await
+    }
+
+    /// Transactions of any of the given jobs, ordered by id.
+    pub async fn transactions_by_jobs(
+        &self, job_seq_numbers: &[i64],
+    ) -> Result<Vec<Transaction>, sqlx::Error> {
+        sqlx::query_as::<_, Transaction>(
+            "SELECT id, body, type, hash, job_seq_number, \"to\", \"from\" FROM transactions WHERE job_seq_number = ANY($1) ORDER BY id",
+        )
+        .bind(job_seq_numbers)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Hash of the compute request transaction of a job, if the job is indexed.
+    pub async fn compute_request_hash(
+        &self, job_seq_number: u64,
+    ) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT hash FROM transactions WHERE job_seq_number = $1 AND type = 'compute_request'",
+        )
+        .bind(to_bigint(job_seq_number)?)
+        .fetch_optional(&self.pool)
+        .await
+    }
+}
+
+pub(super) async fn insert(
+    db_tx: &mut DbTx<'_>, job_seq_number: i64, hash: &str, body: &Value, tx_type: &str, to: &str,
+    from: &str,
+) -> Result<(), sqlx::Error> {
+    let internal_id = format!("{}-{}", tx_type, hash);
+    let result = sqlx::query(
+        "INSERT INTO transactions (job_seq_number, hash, body, type, internal_id, \"to\", \"from\") VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (internal_id) DO NOTHING",
+    )
+    .bind(job_seq_number)
+    .bind(hash)
+    .bind(body)
+    .bind(tx_type)
+    .bind(&internal_id)
+    .bind(to)
+    .bind(from)
+    .execute(&mut **db_tx)
+    .await;
+
+    match result {
+        Ok(result) => {
+            if result.rows_affected() == 0 {
+                log::warn!(
+                    "No rows inserted, possibly due to conflict with internal_id '{}'",
+                    internal_id
+                );
+            } else {
+                log::info!(
+                    "Inserted {} row(s) into transactions table.",
+                    result.rows_affected()
+                );
+            }
+            Ok(())
+        },
+        Err(e) => {
+            log::error!("Error inserting transaction: {}", e);
+            Err(e)
+        },
+    }
+}
+
+pub(super) async fn delete_range(
+    db_tx: &mut DbTx<'_>, from: i64, to: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM transactions
+         WHERE job_seq_number >= $1 AND ($2::BIGINT IS NULL OR job_seq_number < $2)",
+    )
+    .bind(from)
+    .bind(to)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,76 @@
+use super::{state, to_bigint, Storage};
+use crate::types::{IndexedSeedEntry, IndexedTrustEdge};
+
+/// Number of TrustUpdate transactions indexed so far.
+pub(super) const TRUST_CURSOR_KEY: &str = "trust_updates";
+/// Number of SeedUpdate transactions indexed so far.
+pub(super) const SEED_CURSOR_KEY: &str = "seed_updates";
+
+impl Storage {
+    pub async fn commit_trust_edges(
+        &self, edges: &[IndexedTrustEdge], next_key: u64,
+    ) -> Result<(), sqlx::Error> {
+        let next_key = to_bigint(next_key)?;
+        let mut db_tx = self.pool.begin().await?;
+
+        let tx_hashes: Vec<&str> = edges.iter().map(|edge| edge.tx_hash.as_str()).collect();
+        let trust_ids: Vec<&str> = edges.iter().map(|edge| edge.trust_id.as_str()).collect();
+        let froms: Vec<&str> = edges.iter().map(|edge| edge.from.as_str()).collect();
+        let tos: Vec<&str> = edges.iter().map(|edge| edge.to.as_str()).collect();
+        let weights: Vec<f64> = edges.iter().map(|edge| edge.weight).collect();
+        let result = sqlx::query(
+            "INSERT INTO trust_edges (tx_hash, trust_id, \"from\", \"to\", weight)
+             SELECT * FROM UNNEST(
+                $1::VARCHAR[], $2::VARCHAR[], $3::VARCHAR[], $4::VARCHAR[],
+                $5::DOUBLE PRECISION[]
+             )
+             ON CONFLICT (tx_hash, \"from\", \"to\") DO NOTHING",
+        )
+        .bind(&tx_hashes)
+        .bind(&trust_ids)
+        .bind(&froms)
+        .bind(&tos)
+        .bind(&weights)
+        .execute(&mut *db_tx)
+        .await?;
+        log::info!(
+            "Inserted {} row(s) into trust_edges table.",
+            result.rows_affected()
+        );
+
+        state::save_last_processed_key(&mut db_tx, TRUST_CURSOR_KEY, next_key).await?;
+        db_tx.commit().await
+    }
+
+    pub async fn commit_seed_entries(
+        &self, entries: &[IndexedSeedEntry], next_key: u64,
+    ) -> Result<(), sqlx::Error> {
+        let next_key = to_bigint(next_key)?;
+        let mut db_tx = self.pool.begin().await?;
+
+        let tx_hashes: Vec<&str> = entries.iter().map(|entry| entry.tx_hash.as_str()).collect();
+        let seed_ids: Vec<&str> = entries.iter().map(|entry| entry.seed_id.as_str()).collect();
+        let peer_ids: Vec<&str> = entries.iter().map(|entry| entry.peer_id.as_str()).collect();
+        let values: Vec<f64> = entries.iter().map(|entry| entry.value).collect();
+        let result = sqlx::query(
+            "INSERT INTO seed_entries (tx_hash, seed_id, peer_id, value)
+             SELECT * FROM UNNEST(
+                $1::VARCHAR[], $2::VARCHAR[], $3::VARCHAR[], $4::DOUBLE PRECISION[]
+             )
+             ON CONFLICT (tx_hash, peer_id) DO NOTHING",
+        )
+        .bind(&tx_hashes)
+        .bind(&seed_ids)
+        .bind(&peer_ids)
+        .bind(&values)
+        .execute(&mut *db_tx)
+        .await?;
+        log::info!(
+            "Inserted {} row(s) into seed_entries table.",
+            result.rows_affected()
+        );
+
+        state::save_last_processed_key(&mut db_tx, SEED_CURSOR_KEY, next_key).await?;
+        db_tx.commit().await
+    }
+}
@@ -0,0 +1,44 @@
+use super::DbTx;
+use crate::types::IndexedVerification;
+
+pub(super) async fn insert(
+    db_tx: &mut DbTx<'_>, job_seq_number: i64, verifications: &[IndexedVerification],
+) -> Result<(), sqlx::Error> {
+    if verifications.is_empty() {
+        return Ok(());
+    }
+
+    let tx_hashes: Vec<&str> = verifications.iter().map(|v| v.tx_hash.as_str()).collect();
+    let verifiers: Vec<&str> = verifications.iter().map(|v| v.verifier.as_str()).collect();
+    let results: Vec<bool> = verifications.iter().map(|v| v.result).collect();
+    let result = sqlx::query(
+        "INSERT INTO verifications (job_seq_number, tx_hash, verifier, result)
+         SELECT $1, * FROM UNNEST($2::VARCHAR[], $3::VARCHAR[], $4::BOOLEAN[])
+         ON CONFLICT (tx_hash) DO NOTHING",
+    )
+    .bind(job_seq_number)
+    .bind(&tx_hashes)
+    .bind(&verifiers)
+    .bind(&results)
+    .execute(&mut **db_tx)
+    .await?;
+    log::info!(
+        "Inserted {} row(s) into verifications table.",
+        result.rows_affected()
+    );
+    Ok(())
+}
+
+pub(super) async fn delete_range(
+    db_tx: &mut DbTx<'_>, from: i64, to: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM verifications
+         WHERE job_seq_number >= $1 AND ($2::BIGINT IS NULL OR job_seq_number < $2)",
+    )
+    .bind(from)
+    .bind(to)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(result.rows_affected())
+}
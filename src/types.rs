@@ -1,8 +1,409 @@
-use openrank_common::tx::{Tx, TxHash};
+use chrono::{DateTime, Utc};
+use openrank_common::tx::{Body, Tx};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
 
-#[derive(Serialize, Deserialize)]
-pub struct TxWithHash {
+#[derive(Debug, Deserialize)]
+pub struct RpcResponse<T> {
+    #[serde(default)]
+    pub id: Option<u64>,
+    pub result: Option<T>,
+    pub error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonRpcErrorCode {
+    ParseError,
+    InvalidRequest,
+    MethodNotFound,
+    InvalidParams,
+    InternalError,
+    /// Implementation-defined server error, -32099 to -32000.
+    ServerError(i64),
+    Other(i64),
+}
+
+impl JsonRpcError {
+    pub fn kind(&self) -> JsonRpcErrorCode {
+        match self.code {
+            -32700 => JsonRpcErrorCode::ParseError,
+            -32600 => JsonRpcErrorCode::InvalidRequest,
+            -32601 => JsonRpcErrorCode::MethodNotFound,
+            -32602 => JsonRpcErrorCode::InvalidParams,
+            -32603 => JsonRpcErrorCode::InternalError,
+            code @ -32099..=-32000 => JsonRpcErrorCode::ServerError(code),
+            code => JsonRpcErrorCode::Other(code),
+        }
+    }
+
+    /// The sequencer has no error code for a missing item, only a message saying so.
+    pub fn is_not_found(&self) -> bool {
+        let about_call = matches!(
+            self.kind(),
+            JsonRpcErrorCode::ParseError | JsonRpcErrorCode::MethodNotFound
+        );
+        let message = self.message.to_lowercase().replace(['_', ' '], "");
+        !about_call && message.contains("notfound")
+    }
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)?;
+        if let Some(data) = &self.data {
+            write!(f, ": {}", data)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComputeResult {
+    pub compute_commitment_tx_hash: String,
+    pub compute_request_tx_hash: String,
+    #[serde(default)]
+    pub compute_verification_tx_hashes: Vec<String>,
+    pub seq_number: Option<u64>,
+    pub timestamp: u64,
+}
+
+/// How the stored transaction hashes of a job compare with its current compute result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobChange {
+    Unchanged,
+    /// Verifications were added since the job was stored.
+    NewVerifications,
+    Diverged,
+}
+
+impl ComputeResult {
+    /// Commitment, request, then verification hashes, as stored on the job.
+    pub fn transaction_hashes(&self) -> Vec<String> {
+        let mut hashes =
+            vec![self.compute_commitment_tx_hash.clone(), self.compute_request_tx_hash.clone()];
+        hashes.extend(self.compute_verification_tx_hashes.iter().cloned());
+        hashes
+    }
+
+    pub fn compare(&self, stored: &[String]) -> JobChange {
+        let current = self.transaction_hashes();
+        if stored == current.as_slice() {
+            JobChange::Unchanged
+        } else if stored.len() >= 2 && current.starts_with(stored) {
+            JobChange::NewVerifications
+        } else {
+            JobChange::Diverged
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerScore {
+    pub id: String,
+    pub value: f64,
+}
+
+/// Page of `sequencer_get_results`: verification votes and a slice of the scores.
+pub type ResultsPage = (Vec<bool>, Vec<PeerScore>);
+
+/// Query of `sequencer_get_trust_updates` and `sequencer_get_seed_updates`.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdatesQuery {
+    pub from: u64,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxKind {
+    ComputeRequest,
+    ComputeAssignment,
+    ComputeScores,
+    ComputeCommitment,
+    ComputeVerification,
+}
+
+impl TxKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TxKind::ComputeRequest => "compute_request",
+            TxKind::ComputeAssignment => "compute_assignment",
+            TxKind::ComputeScores => "compute_scores",
+            TxKind::ComputeCommitment => "compute_commitment",
+            TxKind::ComputeVerification => "compute_verification",
+        }
+    }
+
+    /// Kind of a transaction body, or `None` for bodies that are not part of a job.
+    pub fn of(body: &Body) -> Option<TxKind> {
+        match body {
+            Body::ComputeRequest(_) => Some(TxKind::ComputeRequest),
+            Body::ComputeAssignment(_) => Some(TxKind::ComputeAssignment),
+            Body::ComputeScores(_) => Some(TxKind::ComputeScores),
+            Body::ComputeCommitment(_) => Some(TxKind::ComputeCommitment),
+            Body::ComputeVerification(_) => Some(TxKind::ComputeVerification),
+            _ => None,
+        }
+    }
+}
+
+/// Renders a hex-serialized value (address, tx hash) as the sequencer's string form.
+pub fn to_hex_string<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    match serde_json::to_value(value)? {
+        Value::String(s) => Ok(s),
+        other => Err(serde::ser::Error::custom(format!(
+            "expected a hex string, got {}",
+            other
+        ))),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchedTx {
+    pub kind: TxKind,
+    pub hash: String,
     pub tx: Tx,
-    pub hash: TxHash,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedTx {
+    pub kind: TxKind,
+    pub hash: String,
+    pub body: Value,
+    pub to: String,
+    pub from: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedScoreEntry {
+    pub tx_hash: String,
+    pub peer_id: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedTrustEdge {
+    pub tx_hash: String,
+    pub trust_id: String,
+    pub from: String,
+    pub to: String,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedSeedEntry {
+    pub tx_hash: String,
+    pub seed_id: String,
+    pub peer_id: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedVerification {
+    pub tx_hash: String,
+    pub verifier: String,
+    pub result: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "varchar", rename_all = "lowercase")]
+pub enum JobStatus {
+    Requested,
+    Assigned,
+    /// Committed, and not yet decided by the verifiers.
+    Committed,
+    /// More approvals than rejections, even if every verifier yet to vote rejects.
+    Verified,
+    /// At least as many rejections as approvals, even if every verifier yet to vote
+    /// approves. A tie counts as rejected.
+    Rejected,
+}
+
+#[derive(Debug, Clone)]
+pub struct IndexedJob {
+    pub seq_number: u64,
+    pub timestamp: DateTime<Utc>,
+    pub transaction_hashes: Vec<String>,
+    pub transactions: Vec<IndexedTx>,
+    pub score_entries: Vec<IndexedScoreEntry>,
+    /// Verifier nodes named by the assignment, whose votes decide the job.
+    pub assigned_verifiers: Vec<String>,
+    pub verifications: Vec<IndexedVerification>,
+}
+
+impl IndexedJob {
+    pub fn status(&self) -> JobStatus {
+        let approvals = self.verifications.iter().filter(|v| v.result).count();
+        let rejections = self.verifications.len() - approvals;
+        let outstanding = self
+            .assigned_verifiers
+            .iter()
+            .filter(|node| !self.verifications.iter().any(|v| &v.verifier == *node))
+            .count();
+        if approvals > rejections + outstanding {
+            return JobStatus::Verified;
+        }
+        if !self.verifications.is_empty() && rejections >= approvals + outstanding {
+            return JobStatus::Rejected;
+        }
+
+        let has = |kind| self.transactions.iter().any(|tx| tx.kind == kind);
+        if has(TxKind::ComputeCommitment) {
+            JobStatus::Committed
+        } else if has(TxKind::ComputeAssignment) {
+            JobStatus::Assigned
+        } else {
+            JobStatus::Requested
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ComputeResult, IndexedJob, IndexedTx, IndexedVerification, JobChange, JobStatus,
+        JsonRpcError, TxKind,
+    };
+    use chrono::{DateTime, Utc};
+    use serde_json::Value;
+
+    fn result(verifications: &[&str]) -> ComputeResult {
+        ComputeResult {
+            compute_commitment_tx_hash: "commitment".to_string(),
+            compute_request_tx_hash: "request".to_string(),
+            compute_verification_tx_hashes: verifications.iter().map(|h| h.to_string()).collect(),
+            seq_number: Some(1),
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn appended_verifications_are_not_divergence() {
+        let stored = result(&["a"]).transaction_hashes();
+        assert_eq!(result(&["a"]).compare(&stored), JobChange::Unchanged);
+        assert_eq!(
+            result(&["a", "b"]).compare(&stored),
+            JobChange::NewVerifications
+        );
+    }
+
+    #[test]
+    fn changed_hashes_are_divergence() {
+        let stored = result(&["a", "b"]).transaction_hashes();
+        assert_eq!(result(&["a"]).compare(&stored), JobChange::Diverged);
+        assert_eq!(result(&["b", "a"]).compare(&stored), JobChange::Diverged);
+
+        let mut other = result(&["a", "b"]);
+        other.compute_commitment_tx_hash = "other".to_string();
+        assert_eq!(other.compare(&stored), JobChange::Diverged);
+        other = result(&["a", "b"]);
+        other.compute_request_tx_hash = "other".to_string();
+        assert_eq!(other.compare(&stored), JobChange::Diverged);
+    }
+
+    fn error(code: i64, message: &str) -> JsonRpcError {
+        JsonRpcError { code, message: message.to_string(), data: None }
+    }
+
+    #[test]
+    fn not_found_messages_are_not_found() {
+        assert!(error(-32000, "ComputeResult not found").is_not_found());
+        assert!(error(-32603, "Internal error: DomainNotFound").is_not_found());
+        assert!(error(-32001, "tx_not_found").is_not_found());
+    }
+
+    #[test]
+    fn internal_error_is_not_not_found() {
+        assert!(!error(-32603, "Internal error").is_not_found());
+        assert!(!error(-32603, "database unavailable").is_not_found());
+    }
+
+    #[test]
+    fn method_not_found_is_not_not_found() {
+        assert!(!error(-32601, "Method not found").is_not_found());
+    }
+
+    #[test]
+    fn parse_error_is_not_not_found() {
+        assert!(!error(-32700, "Parse error: key not found").is_not_found());
+    }
+
+    #[test]
+    fn invalid_params_with_another_message_is_not_not_found() {
+        assert!(!error(-32602, "Invalid params: expected u64").is_not_found());
+    }
+
+    /// A committed job assigned to `verifiers` verifiers, with `votes` cast by the first ones.
+    fn voted_job(verifiers: usize, votes: &[bool]) -> IndexedJob {
+        let node = |i: usize| format!("0x{:040x}", i);
+        IndexedJob {
+            seq_number: 1,
+            timestamp: DateTime::<Utc>::UNIX_EPOCH,
+            transaction_hashes: Vec::new(),
+            transactions: vec![IndexedTx {
+                kind: TxKind::ComputeCommitment,
+                hash: "commitment".to_string(),
+                body: Value::Null,
+                to: String::new(),
+                from: String::new(),
+            }],
+            score_entries: Vec::new(),
+            assigned_verifiers: (0..verifiers).map(node).collect(),
+            verifications: votes
+                .iter()
+                .enumerate()
+                .map(|(i, &result)| IndexedVerification {
+                    tx_hash: format!("verification {}", i),
+                    verifier: node(i),
+                    result,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn partial_vote_is_not_final() {
+        assert_eq!(voted_job(3, &[]).status(), JobStatus::Committed);
+        assert_eq!(voted_job(3, &[false]).status(), JobStatus::Committed);
+        assert_eq!(voted_job(3, &[true]).status(), JobStatus::Committed);
+        assert_eq!(voted_job(4, &[true, false]).status(), JobStatus::Committed);
+    }
+
+    #[test]
+    fn partial_vote_with_a_settled_majority_is_final() {
+        assert_eq!(voted_job(3, &[true, true]).status(), JobStatus::Verified);
+        assert_eq!(voted_job(3, &[false, false]).status(), JobStatus::Rejected);
+        // Two rejections out of four can at best end in a tie.
+        assert_eq!(voted_job(4, &[false, false]).status(), JobStatus::Rejected);
+    }
+
+    #[test]
+    fn full_vote_is_decided_by_majority() {
+        assert_eq!(
+            voted_job(3, &[false, true, true]).status(),
+            JobStatus::Verified
+        );
+        assert_eq!(
+            voted_job(3, &[true, false, false]).status(),
+            JobStatus::Rejected
+        );
+    }
+
+    #[test]
+    fn tie_is_rejected() {
+        assert_eq!(voted_job(2, &[true, false]).status(), JobStatus::Rejected);
+        assert_eq!(
+            voted_job(4, &[true, false, true, false]).status(),
+            JobStatus::Rejected
+        );
+    }
 }